@@ -0,0 +1,197 @@
+use crate::{canvas::Canvas, color::Color, Float};
+use num_traits::Zero;
+
+// running per-pixel statistics, updated with welford's algorithm so samples can
+// be streamed in without keeping them around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccumPixel {
+    pub count: u32,
+    pub mean: Color,
+    m2: Color,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccumBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<AccumPixel>,
+}
+
+impl AccumPixel {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            mean: Color::zero(),
+            m2: Color::zero(),
+        }
+    }
+
+    pub fn add_sample(&mut self, sample: Color) {
+        self.count += 1;
+        let delta = sample - self.mean;
+        self.mean += delta * (1.0 / self.count as Float);
+        self.m2 += delta * (sample - self.mean);
+    }
+
+    // sample variance per channel, zero until there are at least two samples.
+    pub fn variance(&self) -> Color {
+        if self.count < 2 {
+            return Color::zero();
+        }
+
+        self.m2 * (1.0 / (self.count - 1) as Float)
+    }
+
+    pub fn merge(&mut self, other: &AccumPixel) {
+        if other.count == 0 {
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let weight = other.count as Float / count as Float;
+
+        self.mean += delta * weight;
+        self.m2 += other.m2 + delta * delta * (self.count as Float * weight);
+        self.count = count;
+    }
+}
+
+impl AccumBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![AccumPixel::new(); width * height],
+        }
+    }
+
+    pub fn add_sample(&mut self, x: usize, y: usize, sample: Color) -> &Self {
+        self[(x, y)].add_sample(sample);
+        self
+    }
+
+    pub fn merge(&mut self, other: &AccumBuffer) {
+        if self.width != other.width || self.height != other.height {
+            panic!(
+                "cannot merge accumulation buffers of different sizes ({}, {}) and ({}, {})",
+                self.width, self.height, other.width, other.height
+            );
+        }
+
+        for (pixel, other) in self.pixels.iter_mut().zip(other.pixels.iter()) {
+            pixel.merge(other);
+        }
+    }
+
+    pub fn to_canvas(&self) -> Canvas {
+        self.map_to_canvas(|p| p.mean)
+    }
+
+    pub fn variance_canvas(&self) -> Canvas {
+        self.map_to_canvas(|p| p.variance())
+    }
+
+    fn map_to_canvas(&self, f: impl Fn(&AccumPixel) -> Color) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height);
+        for (target, pixel) in canvas.pixels.iter_mut().zip(self.pixels.iter()) {
+            *target = f(pixel);
+        }
+
+        canvas
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for AccumBuffer {
+    type Output = AccumPixel;
+
+    fn index(&self, (x, y): (usize, usize)) -> &AccumPixel {
+        match self.pixels.get(x + y * self.width) {
+            Some(t) => t,
+            None => panic!(
+                "out of bounds! tried to get index of ({}, {}) for buffer size ({}, {})",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for AccumBuffer {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut AccumPixel {
+        match self.pixels.get_mut(x + y * self.width) {
+            Some(t) => t,
+            None => panic!(
+                "out of bounds! tried to get index of ({}, {}) for buffer size ({}, {})",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_mean_and_variance() {
+        let mut buffer = AccumBuffer::new(2, 2);
+        buffer.add_sample(1, 0, Color::new(1.0, 0.0, 2.0));
+        buffer.add_sample(1, 0, Color::new(3.0, 0.0, 2.0));
+        buffer.add_sample(1, 0, Color::new(5.0, 0.0, 2.0));
+
+        assert_eq!(buffer[(1, 0)].count, 3);
+        assert_eq!(buffer[(1, 0)].mean, Color::new(3.0, 0.0, 2.0));
+        assert_eq!(buffer[(1, 0)].variance(), Color::new(4.0, 0.0, 0.0));
+        assert_eq!(buffer[(0, 0)].count, 0);
+    }
+
+    #[test]
+    fn test_single_sample_variance() {
+        let mut buffer = AccumBuffer::new(1, 1);
+        buffer.add_sample(0, 0, Color::new(0.5, 0.5, 0.5));
+
+        assert_eq!(buffer.variance_canvas()[(0, 0)], Color::zero());
+        assert_eq!(buffer.to_canvas()[(0, 0)], Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_merge() {
+        let samples = [1.0, 2.0, 4.0, 7.0, 11.0];
+        let mut all = AccumBuffer::new(1, 1);
+        let mut a = AccumBuffer::new(1, 1);
+        let mut b = AccumBuffer::new(1, 1);
+
+        for (i, s) in samples.iter().enumerate() {
+            let c = Color::new(*s, -*s, 0.0);
+            all.add_sample(0, 0, c);
+            if i < 2 {
+                a.add_sample(0, 0, c);
+            } else {
+                b.add_sample(0, 0, c);
+            }
+        }
+
+        a.merge(&b);
+
+        assert_eq!(a[(0, 0)].count, 5);
+        assert_float_eq!(a[(0, 0)].mean, all[(0, 0)].mean, abs_all <= 1e-9);
+        assert_float_eq!(
+            a[(0, 0)].variance(),
+            all[(0, 0)].variance(),
+            abs_all <= 1e-9
+        );
+        assert_float_eq!(
+            a[(0, 0)].variance(),
+            Color::new(16.5, 16.5, 0.0),
+            abs_all <= 1e-9
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot merge accumulation buffers of different sizes")]
+    fn test_merge_size_mismatch() {
+        let mut a = AccumBuffer::new(1, 1);
+        a.merge(&AccumBuffer::new(2, 1));
+    }
+}
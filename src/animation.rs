@@ -0,0 +1,803 @@
+use crate::{
+    canvas::Canvas,
+    color::Color,
+    error::{Error, Result},
+    matrix::Matrix4,
+    transformation::Decomposition,
+    tuple::Tuple,
+    Float,
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+// settings for encoding a sequence of canvases as an animated gif. `delay` is
+// the time each frame is shown in hundredths of a second, and `repeat` is how
+// many times the animation loops, with 0 looping forever.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gif {
+    pub delay: u16,
+    pub repeat: u16,
+}
+
+impl Default for Gif {
+    fn default() -> Self {
+        Self {
+            delay: 4,
+            repeat: 0,
+        }
+    }
+}
+
+impl Gif {
+    // all frames share one 256 color palette, picked with median cut over every
+    // pixel of the sequence.
+    pub fn encode(&self, frames: &[Canvas]) -> Vec<u8> {
+        let (width, height) = match frames.first() {
+            Some(frame) => (frame.width, frame.height),
+            None => panic!("cannot encode an animation without frames!"),
+        };
+        if frames
+            .iter()
+            .any(|f| f.width != width || f.height != height)
+        {
+            panic!("cannot encode frames of different sizes!");
+        }
+        assert!(
+            width <= u16::MAX as usize && height <= u16::MAX as usize,
+            "frames too large for gif!"
+        );
+
+        let frames_rgb: Vec<Vec<[u8; 3]>> = frames.iter().map(rgb8).collect();
+        let palette = median_cut(&frames_rgb, 256);
+
+        let mut buffer = b"GIF89a".to_vec();
+        buffer.extend_from_slice(&(width as u16).to_le_bytes());
+        buffer.extend_from_slice(&(height as u16).to_le_bytes());
+        // global color table of 2^(7 + 1) entries, 8 bits per channel.
+        buffer.extend_from_slice(&[0xf7, 0, 0]);
+        for i in 0..256 {
+            buffer.extend_from_slice(&palette.get(i).copied().unwrap_or([0, 0, 0]));
+        }
+
+        buffer.extend_from_slice(&[0x21, 0xff, 0x0b]);
+        buffer.extend_from_slice(b"NETSCAPE2.0");
+        buffer.extend_from_slice(&[0x03, 0x01]);
+        buffer.extend_from_slice(&self.repeat.to_le_bytes());
+        buffer.push(0);
+
+        let mut cache = HashMap::new();
+        for frame in frames_rgb.iter() {
+            buffer.extend_from_slice(&[0x21, 0xf9, 0x04, 0]);
+            buffer.extend_from_slice(&self.delay.to_le_bytes());
+            buffer.extend_from_slice(&[0, 0]);
+
+            buffer.extend_from_slice(&[0x2c, 0, 0, 0, 0]);
+            buffer.extend_from_slice(&(width as u16).to_le_bytes());
+            buffer.extend_from_slice(&(height as u16).to_le_bytes());
+            buffer.push(0);
+
+            let indices: Vec<u8> = frame
+                .iter()
+                .map(|px| *cache.entry(*px).or_insert_with(|| nearest(&palette, *px)))
+                .collect();
+
+            buffer.push(8);
+            for block in lzw_encode(&indices).chunks(255) {
+                buffer.push(block.len() as u8);
+                buffer.extend_from_slice(block);
+            }
+            buffer.push(0);
+        }
+
+        buffer.push(0x3b);
+        buffer
+    }
+
+    pub fn write(&self, frames: &[Canvas], path: &Path) -> Result<()> {
+        let mut f = BufWriter::new(File::create(path)?);
+        f.write_all(&self.encode(frames))?;
+        f.flush()?;
+
+        Ok(())
+    }
+}
+
+// renders `frames` frames and writes each one to `path_pattern` with the run of
+// `#` in it replaced by the zero-padded frame number, so "out/frame_####.ppm"
+// gives out/frame_0001.ppm and so on. frames are numbered from 1. the output
+// format follows the extension (ppm, hdr, qoi or tga). `progress` is called
+// with the frame number and total after each frame is written.
+pub fn render_sequence(
+    frames: usize,
+    path_pattern: &str,
+    mut render: impl FnMut(usize) -> Canvas,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<PathBuf>> {
+    let extension = Path::new(path_pattern)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !["ppm", "hdr", "qoi", "tga"].contains(&extension.as_str()) {
+        return Err(Error::UnsupportedFormat(extension));
+    }
+
+    let mut paths = Vec::with_capacity(frames);
+    for frame in 1..=frames {
+        let path = PathBuf::from(frame_path(path_pattern, frame));
+        let canvas = render(frame);
+
+        match extension.as_str() {
+            "ppm" => canvas.write_to_ppm(&path).map(|_| ())?,
+            "hdr" => canvas.write_to_hdr(&path)?,
+            "qoi" => canvas.write_to_qoi(&path)?,
+            _ => canvas.write_to_tga(&path)?,
+        }
+
+        progress(frame, frames);
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+// replaces the last run of `#` with the zero-padded frame number, or appends
+// the number before the extension if there is none.
+fn frame_path(pattern: &str, frame: usize) -> String {
+    match pattern.rfind('#') {
+        Some(end) => {
+            let start = pattern[..end].trim_end_matches('#').len();
+            format!(
+                "{}{:0width$}{}",
+                &pattern[..start],
+                frame,
+                &pattern[end + 1..],
+                width = end + 1 - start
+            )
+        }
+        None => match pattern.rfind('.') {
+            Some(dot) => format!("{}_{:04}{}", &pattern[..dot], frame, &pattern[dot..]),
+            None => format!("{}_{:04}", pattern, frame),
+        },
+    }
+}
+
+// values that can be keyed on a `Track`. everything, including the spline,
+// is built out of `lerp`.
+pub trait Interpolate: Copy {
+    fn lerp(a: Self, b: Self, t: Float) -> Self;
+}
+
+impl Interpolate for Float {
+    fn lerp(a: Self, b: Self, t: Float) -> Self {
+        a + (b - a) * t
+    }
+}
+
+impl Interpolate for Tuple {
+    fn lerp(a: Self, b: Self, t: Float) -> Self {
+        Tuple::lerp(a, b, t)
+    }
+}
+
+impl Interpolate for Color {
+    fn lerp(a: Self, b: Self, t: Float) -> Self {
+        Color::lerp(a, b, t)
+    }
+}
+
+// translation, shear and scale are lerped, rotation is slerped as a
+// quaternion so it stays a rotation all the way between keys. keying
+// `Matrix4::decompose` results animates a transform; `compose` turns a sample
+// back into a matrix.
+impl Interpolate for Decomposition {
+    fn lerp(a: Self, b: Self, t: Float) -> Self {
+        Decomposition {
+            translation: Tuple::lerp(a.translation, b.translation, t),
+            rotation: from_quaternion(slerp(
+                to_quaternion(&a.rotation),
+                to_quaternion(&b.rotation),
+                t,
+            )),
+            shear: Tuple::lerp(a.shear, b.shear, t),
+            scale: Tuple::lerp(a.scale, b.scale, t),
+        }
+    }
+}
+
+// unit quaternion as [w, x, y, z] from the upper 3x3 of a rotation matrix.
+fn to_quaternion(m: &Matrix4) -> [Float; 4] {
+    let trace = m[(0, 0)] + m[(1, 1)] + m[(2, 2)];
+
+    // build from the largest component to keep the division well conditioned.
+    if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [
+            s / 4.0,
+            (m[(2, 1)] - m[(1, 2)]) / s,
+            (m[(0, 2)] - m[(2, 0)]) / s,
+            (m[(1, 0)] - m[(0, 1)]) / s,
+        ]
+    } else if m[(0, 0)] > m[(1, 1)] && m[(0, 0)] > m[(2, 2)] {
+        let s = (1.0 + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt() * 2.0;
+        [
+            (m[(2, 1)] - m[(1, 2)]) / s,
+            s / 4.0,
+            (m[(0, 1)] + m[(1, 0)]) / s,
+            (m[(0, 2)] + m[(2, 0)]) / s,
+        ]
+    } else if m[(1, 1)] > m[(2, 2)] {
+        let s = (1.0 + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt() * 2.0;
+        [
+            (m[(0, 2)] - m[(2, 0)]) / s,
+            (m[(0, 1)] + m[(1, 0)]) / s,
+            s / 4.0,
+            (m[(1, 2)] + m[(2, 1)]) / s,
+        ]
+    } else {
+        let s = (1.0 + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt() * 2.0;
+        [
+            (m[(1, 0)] - m[(0, 1)]) / s,
+            (m[(0, 2)] + m[(2, 0)]) / s,
+            (m[(1, 2)] + m[(2, 1)]) / s,
+            s / 4.0,
+        ]
+    }
+}
+
+fn from_quaternion([w, x, y, z]: [Float; 4]) -> Matrix4 {
+    Matrix4::new([
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - w * z),
+            2.0 * (x * z + w * y),
+            0.0,
+        ],
+        [
+            2.0 * (x * y + w * z),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - w * x),
+            0.0,
+        ],
+        [
+            2.0 * (x * z - w * y),
+            2.0 * (y * z + w * x),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+        ],
+        [0.0, 0.0, 0.0, 1.0],
+    ])
+}
+
+// takes the short way around. `t` outside [0, 1] extrapolates, which the
+// catmull-rom pyramid relies on.
+fn slerp(a: [Float; 4], mut b: [Float; 4], t: Float) -> [Float; 4] {
+    let mut cos = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<Float>();
+    if cos < 0.0 {
+        b = b.map(|v| -v);
+        cos = -cos;
+    }
+
+    let (wa, wb) = if cos > 0.9995 {
+        // nearly parallel, where sin(angle) is too small to divide by.
+        (1.0 - t, t)
+    } else {
+        let angle = cos.acos();
+        let sin = angle.sin();
+        (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+
+    let q = [0, 1, 2, 3].map(|i| a[i] * wa + b[i] * wb);
+    let norm = q.iter().map(|v| v * v).sum::<Float>().sqrt();
+    q.map(|v| v / norm)
+}
+
+// how a track moves from one key to the next, set on the earlier key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    // hold the value until the next key.
+    Step,
+    Linear,
+    // smoothstep, easing in and out of both keys.
+    EaseInOut,
+    // uniform catmull-rom through the neighbouring keys, so motion passes
+    // through every key without stopping at it.
+    CatmullRom,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: Float,
+    pub value: T,
+    pub interpolation: Interpolation,
+}
+
+// keys sorted by time. sampling before the first or after the last key holds
+// the value of that key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    pub keys: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: vec![] }
+    }
+}
+
+impl<T: Interpolate> Track<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // replaces any key already at `time`.
+    pub fn insert(&mut self, time: Float, value: T, interpolation: Interpolation) -> &mut Self {
+        let key = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+
+        match self.keys.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(i) => self.keys[i] = key,
+            Err(i) => self.keys.insert(i, key),
+        }
+
+        self
+    }
+
+    pub fn sample(&self, time: Float) -> Option<T> {
+        let first = self.keys.first()?;
+        let last = self.keys.last()?;
+        if time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+
+        let i = self.keys.partition_point(|k| k.time <= time) - 1;
+        let (a, b) = (&self.keys[i], &self.keys[i + 1]);
+        let t = (time - a.time) / (b.time - a.time);
+
+        Some(match a.interpolation {
+            Interpolation::Step => a.value,
+            Interpolation::Linear => T::lerp(a.value, b.value, t),
+            Interpolation::EaseInOut => T::lerp(a.value, b.value, t * t * (3.0 - 2.0 * t)),
+            Interpolation::CatmullRom => {
+                let p0 = self.keys[i.saturating_sub(1)].value;
+                let p3 = self.keys[(i + 2).min(self.keys.len() - 1)].value;
+
+                // barry-goldman pyramid with knots at -1, 0, 1 and 2.
+                let a1 = T::lerp(p0, a.value, t + 1.0);
+                let a2 = T::lerp(a.value, b.value, t);
+                let a3 = T::lerp(b.value, p3, t - 1.0);
+                let b1 = T::lerp(a1, a2, (t + 1.0) / 2.0);
+                let b2 = T::lerp(a2, a3, t / 2.0);
+                T::lerp(b1, b2, t)
+            }
+        })
+    }
+}
+
+fn rgb8(canvas: &Canvas) -> Vec<[u8; 3]> {
+    canvas
+        .pixels
+        .iter()
+        .map(|p| {
+            let p = p.to_int(255);
+            [p.r as u8, p.g as u8, p.b as u8]
+        })
+        .collect()
+}
+
+// splits the color space into at most `size` boxes, always cutting the box with
+// the widest channel range at its pixel-weighted median. each palette entry is
+// the weighted average of its box.
+fn median_cut(frames: &[Vec<[u8; 3]>], size: usize) -> Vec<[u8; 3]> {
+    let mut counts: HashMap<[u8; 3], u64> = HashMap::new();
+    for px in frames.iter().flatten() {
+        *counts.entry(*px).or_insert(0) += 1;
+    }
+
+    let mut colors: Vec<([u8; 3], u64)> = counts.into_iter().collect();
+    colors.sort_unstable();
+    let mut boxes = vec![colors];
+
+    let range = |colors: &[([u8; 3], u64)], channel: usize| {
+        let (min, max) = colors.iter().fold((255, 0), |(min, max), (c, _)| {
+            (c[channel].min(min), c[channel].max(max))
+        });
+        max.saturating_sub(min)
+    };
+
+    while boxes.len() < size {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| {
+                let channel = (0..3).max_by_key(|c| range(b, *c)).unwrap_or(0);
+                (i, channel, range(b, channel))
+            })
+            .max_by_key(|(_, _, r)| *r);
+
+        let Some((i, channel, _)) = widest else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(i);
+        colors.sort_unstable_by_key(|(c, _)| c[channel]);
+
+        let total: u64 = colors.iter().map(|(_, n)| n).sum();
+        let mut seen = 0;
+        let mut split = 1;
+        for (j, (_, n)) in colors.iter().enumerate() {
+            seen += n;
+            if seen * 2 >= total {
+                split = (j + 1).clamp(1, colors.len() - 1);
+                break;
+            }
+        }
+
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    boxes
+        .iter()
+        .filter(|b| !b.is_empty())
+        .map(|b| {
+            let total: u64 = b.iter().map(|(_, n)| n).sum();
+            let mut avg = [0u8; 3];
+            for (channel, value) in avg.iter_mut().enumerate() {
+                let sum: u64 = b.iter().map(|(c, n)| c[channel] as u64 * n).sum();
+                *value = ((sum + total / 2) / total) as u8;
+            }
+            avg
+        })
+        .collect()
+}
+
+fn nearest(palette: &[[u8; 3]], px: [u8; 3]) -> u8 {
+    let distance =
+        |c: &[u8; 3]| -> i32 { (0..3).map(|i| (c[i] as i32 - px[i] as i32).pow(2)).sum() };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| distance(c))
+        .map_or(0, |(i, _)| i as u8)
+}
+
+// variable-width lzw with a minimum code size of 8, following giflib's encoder:
+// the code width grows once the next free code no longer fits, and the table is
+// reset with a clear code when it fills up at 4095 entries.
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const EOI: u16 = 257;
+    const MAX_CODE: u16 = 4095;
+
+    let mut out = Vec::new();
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut code_size = 9;
+    let mut next_code = EOI + 1;
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+
+    let mut emit = |code: u16, code_size: &mut u32, next_code: u16| {
+        bits |= (code as u32) << bit_count;
+        bit_count += *code_size;
+        while bit_count >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            bit_count -= 8;
+        }
+        if next_code >= 1 << *code_size && *code_size < 12 {
+            *code_size += 1;
+        }
+    };
+
+    emit(CLEAR, &mut code_size, next_code);
+
+    if let Some((&first, rest)) = indices.split_first() {
+        let mut current = first as u16;
+
+        for &k in rest {
+            if let Some(&code) = table.get(&(current, k)) {
+                current = code;
+                continue;
+            }
+
+            emit(current, &mut code_size, next_code);
+
+            if next_code >= MAX_CODE {
+                emit(CLEAR, &mut code_size, next_code);
+                table.clear();
+                next_code = EOI + 1;
+                code_size = 9;
+            } else {
+                table.insert((current, k), next_code);
+                next_code += 1;
+            }
+
+            current = k as u16;
+        }
+
+        emit(current, &mut code_size, next_code);
+    }
+
+    emit(EOI, &mut code_size, next_code);
+    if bit_count > 0 {
+        out.push(bits as u8);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        consts::PI,
+        sampling::{Rng, XorShift},
+        transformation::{rotation_y, rotation_z, scaling, translation},
+    };
+
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let mut table: Vec<Vec<u8>> = (0..=255).map(|i| vec![i]).collect();
+        table.extend([vec![], vec![]]);
+        let (mut pos, mut code_size) = (0, 9);
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = vec![];
+
+        loop {
+            let mut code = 0usize;
+            for bit in 0..code_size {
+                code |= ((data[(pos + bit) / 8] >> ((pos + bit) % 8)) as usize & 1) << bit;
+            }
+            pos += code_size;
+
+            match code {
+                256 => {
+                    table.truncate(258);
+                    code_size = 9;
+                    prev = None;
+                    continue;
+                }
+                257 => break,
+                _ => {}
+            }
+
+            let entry = match table.get(code) {
+                Some(entry) => entry.clone(),
+                None => {
+                    let mut entry = prev.clone().unwrap();
+                    entry.push(entry[0]);
+                    entry
+                }
+            };
+            out.extend_from_slice(&entry);
+
+            if let Some(mut p) = prev {
+                if table.len() < 4096 {
+                    p.push(entry[0]);
+                    table.push(p);
+                }
+            }
+            prev = Some(entry);
+
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        }
+
+        out
+    }
+
+    #[test]
+    fn test_lzw_roundtrip() {
+        let mut rng = XorShift::new(7);
+        let noise: Vec<u8> = (0..20000).map(|_| rng.next_u64() as u8).collect();
+        let runs: Vec<u8> = (0..20000).map(|i| (i / 300) as u8).collect();
+
+        assert_eq!(lzw_decode(&lzw_encode(&noise)), noise);
+        assert_eq!(lzw_decode(&lzw_encode(&runs)), runs);
+        assert_eq!(lzw_decode(&lzw_encode(&[])), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_median_cut() {
+        let few = vec![vec![[255, 0, 0], [0, 0, 255], [255, 0, 0]]];
+        let mut palette = median_cut(&few, 256);
+        palette.sort();
+        assert_eq!(palette, vec![[0, 0, 255], [255, 0, 0]]);
+
+        let many = vec![(0..=255u8)
+            .flat_map(|r| [[r, 0, 0], [r, 255, 128]])
+            .collect::<Vec<_>>()];
+        let palette = median_cut(&many, 256);
+        assert_eq!(palette.len(), 256);
+        assert!(many[0]
+            .iter()
+            .all(|px| { palette[nearest(&palette, *px) as usize][0].abs_diff(px[0]) <= 2 }));
+    }
+
+    #[test]
+    fn test_encode_gif() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+        let mut a = Canvas::new(3, 2);
+        a.fill(red);
+        let mut b = a.clone();
+        b.write_pixel(1, 1, green);
+
+        let gif = Gif {
+            delay: 10,
+            repeat: 0,
+        }
+        .encode(&[a, b]);
+
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(&gif[6..11], &[3, 0, 2, 0, 0xf7]);
+        assert_eq!(*gif.last().unwrap(), 0x3b);
+
+        // walk past the color table and looping extension to each frame.
+        let palette: Vec<&[u8]> = gif[13..13 + 768].chunks(3).collect();
+        let mut pos = 13 + 768 + 19;
+        let mut frames = vec![];
+        while gif[pos] == 0x21 {
+            assert_eq!(&gif[pos..pos + 6], &[0x21, 0xf9, 0x04, 0, 10, 0]);
+            pos += 8;
+            assert_eq!(&gif[pos..pos + 10], &[0x2c, 0, 0, 0, 0, 3, 0, 2, 0, 0]);
+            assert_eq!(gif[pos + 10], 8);
+            pos += 11;
+
+            let mut data = vec![];
+            while gif[pos] != 0 {
+                data.extend_from_slice(&gif[pos + 1..pos + 1 + gif[pos] as usize]);
+                pos += gif[pos] as usize + 1;
+            }
+            pos += 1;
+            frames.push(lzw_decode(&data));
+        }
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(gif[pos], 0x3b);
+        assert!(frames[0]
+            .iter()
+            .all(|i| palette[*i as usize] == [255, 0, 0]));
+        assert_eq!(palette[frames[1][4] as usize], &[0, 255, 0]);
+        assert_eq!(palette[frames[1][3] as usize], &[255, 0, 0]);
+    }
+
+    #[test]
+    fn test_frame_path() {
+        assert_eq!(frame_path("frame_####.ppm", 7), "frame_0007.ppm");
+        assert_eq!(frame_path("out/#/f##.qoi", 123), "out/#/f123.qoi");
+        assert_eq!(frame_path("render.tga", 2), "render_0002.tga");
+    }
+
+    #[test]
+    fn test_render_sequence() {
+        let dir = std::env::temp_dir().join("renachan_test_render_sequence");
+        std::fs::create_dir_all(&dir).unwrap();
+        let pattern = dir.join("frame_###.ppm");
+
+        let mut calls = vec![];
+        let paths = render_sequence(
+            3,
+            pattern.to_str().unwrap(),
+            |frame| {
+                let mut c = Canvas::new(2, 2);
+                c.fill(Color::new(frame as Float / 3.0, 0.0, 0.0));
+                c
+            },
+            |frame, total| calls.push((frame, total)),
+        )
+        .unwrap();
+
+        assert_eq!(calls, vec![(1, 3), (2, 3), (3, 3)]);
+        assert_eq!(paths[2], dir.join("frame_003.ppm"));
+        let last = Canvas::from_ppm(File::open(&paths[2]).unwrap()).unwrap();
+        assert_eq!(last[(1, 1)], Color::new(1.0, 0.0, 0.0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            render_sequence(1, "frame_#.png", |_| unreachable!(), |_, _| {}),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_track_linear_step() {
+        let mut track = Track::new();
+        track
+            .insert(0.0, 0.0, Interpolation::Linear)
+            .insert(2.0, 10.0, Interpolation::Step)
+            .insert(4.0, 20.0, Interpolation::Linear);
+
+        assert_eq!(track.sample(-1.0), Some(0.0));
+        assert_eq!(track.sample(1.0), Some(5.0));
+        assert_eq!(track.sample(3.0), Some(10.0));
+        assert_eq!(track.sample(4.0), Some(20.0));
+        assert_eq!(track.sample(9.0), Some(20.0));
+        assert_eq!(Track::<Float>::new().sample(0.0), None);
+
+        track.insert(2.0, 12.0, Interpolation::Linear);
+        assert_eq!(track.keys.len(), 3);
+        assert_eq!(track.sample(3.0), Some(16.0));
+    }
+
+    #[test]
+    fn test_track_ease_spline() {
+        let mut ease = Track::new();
+        ease.insert(0.0, Color::new(0.0, 0.0, 0.0), Interpolation::EaseInOut)
+            .insert(1.0, Color::new(1.0, 1.0, 1.0), Interpolation::Linear);
+
+        assert_eq!(ease.sample(0.5), Some(Color::new(0.5, 0.5, 0.5)));
+        assert_eq!(
+            ease.sample(0.25),
+            Some(Color::new(0.15625, 0.15625, 0.15625))
+        );
+
+        // a spline through evenly spaced points on a line stays on the line.
+        let mut spline = Track::new();
+        for i in 0..4 {
+            let x = i as Float;
+            spline.insert(x, Tuple::point(x, 2.0 * x, 0.0), Interpolation::CatmullRom);
+        }
+
+        assert_eq!(spline.sample(1.5), Some(Tuple::point(1.5, 3.0, 0.0)));
+        assert_eq!(spline.sample(2.0), Some(Tuple::point(2.0, 4.0, 0.0)));
+        assert_eq!(spline.sample(0.0), Some(Tuple::point(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_track_transform() {
+        let start = translation(0.0, 0.0, 0.0) * rotation_y(0.0) * scaling(1.0, 1.0, 1.0);
+        let end = translation(2.0, 4.0, 0.0) * rotation_y(PI / 2.0) * scaling(3.0, 3.0, 3.0);
+
+        let mut track = Track::new();
+        track
+            .insert(0.0, start.decompose().unwrap(), Interpolation::Linear)
+            .insert(1.0, end.decompose().unwrap(), Interpolation::Linear);
+
+        // halfway is a quarter turn, not an average of matrix entries, which
+        // would shrink the object as it turned.
+        let halfway = track.sample(0.5).unwrap().compose();
+        assert_eq!(
+            halfway,
+            translation(1.0, 2.0, 0.0) * rotation_y(PI / 4.0) * scaling(2.0, 2.0, 2.0)
+        );
+        assert_eq!(track.sample(1.0).unwrap().compose(), end);
+
+        // the spline goes through every key and keeps the rotation rigid.
+        let mut spline = Track::new();
+        for i in 0..4 {
+            let angle = i as Float * PI / 4.0;
+            spline.insert(
+                i as Float,
+                rotation_z(angle).decompose().unwrap(),
+                Interpolation::CatmullRom,
+            );
+        }
+        assert_eq!(spline.sample(2.0).unwrap().compose(), rotation_z(PI / 2.0));
+        assert_eq!(
+            spline.sample(1.5).unwrap().compose(),
+            rotation_z(3.0 * PI / 8.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot encode frames of different sizes!")]
+    fn test_encode_gif_size_mismatch() {
+        Gif::default().encode(&[Canvas::new(2, 2), Canvas::new(2, 3)]);
+    }
+}
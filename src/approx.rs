@@ -0,0 +1,59 @@
+use crate::Float;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(not(feature = "f32"))]
+pub const DEFAULT_EPSILON: Float = 0.00001;
+
+// f32 only has ~7 significant digits, so the default has to be looser.
+#[cfg(feature = "f32")]
+pub const DEFAULT_EPSILON: Float = 0.0001;
+
+// stored as raw f64 bits since there's no atomic float. the casts are no-ops
+// unless the `f32` feature is on.
+#[allow(clippy::unnecessary_cast)]
+static EPSILON: AtomicU64 = AtomicU64::new((DEFAULT_EPSILON as f64).to_bits());
+
+// absolute tolerance used by `PartialEq` on tuples, colors and matrices. this
+// is the one knob for floating point slop in the crate: anything that needs
+// to nudge points off a surface should use it too, so that scenes at large
+// scales only need this raised.
+pub fn epsilon() -> Float {
+    f64::from_bits(EPSILON.load(Ordering::Relaxed)) as Float
+}
+
+pub fn set_epsilon(epsilon: Float) {
+    if epsilon.is_nan() || epsilon < 0.0 {
+        panic!("epsilon must be a non-negative number, got {}", epsilon);
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    EPSILON.store((epsilon as f64).to_bits(), Ordering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_epsilon() {
+        assert_eq!(epsilon(), DEFAULT_EPSILON);
+    }
+
+    #[test]
+    #[should_panic(expected = "epsilon must be a non-negative number")]
+    fn test_set_negative_epsilon() {
+        set_epsilon(-1.0);
+    }
+
+    #[test]
+    fn test_epsilon_applies_to_colors() {
+        use crate::color::Color;
+
+        let a = Color::new(0.5, 0.5, 0.5);
+        let b = Color::new(0.5 + DEFAULT_EPSILON / 2.0, 0.5, 0.5);
+        let c = Color::new(0.5 + DEFAULT_EPSILON * 2.0, 0.5, 0.5);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
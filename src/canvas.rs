@@ -1,11 +1,49 @@
-use crate::color::Color;
-use std::{fs::File, io::Write, path::Path};
+use crate::{
+    color::{Color, ToneMap, Transfer},
+    error::{Error, Result},
+    Float,
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::Path,
+};
+
+// how `Canvas::blit` combines source pixels with what's already there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Blend {
+    // overwrite color and alpha.
+    Replace,
+    // porter-duff "over" using the source alpha.
+    Over,
+}
+
+// resampling filter used by `Canvas::resize`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+    pub width: usize,
+    pub height: usize,
+    // largest per-channel absolute difference over the whole image.
+    pub max_error: Float,
+    pub mean_squared_error: Float,
+    // largest per-channel absolute difference for each pixel, row-major.
+    pub errors: Vec<Float>,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Canvas {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<Color>,
+    // per-pixel coverage in [0, 1], laid out like `pixels`. `None` means the
+    // canvas is fully opaque.
+    pub alpha: Option<Vec<Float>>,
 }
 
 impl Canvas {
@@ -14,7 +52,280 @@ impl Canvas {
             width,
             height,
             pixels: vec![Color::new(0.0, 0.0, 0.0); width * height],
+            alpha: None,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> Color) -> Self {
+        let mut canvas = Self::new(width, height);
+        for (i, pixel) in canvas.pixels.iter_mut().enumerate() {
+            *pixel = f(i % width, i / width);
+        }
+
+        canvas
+    }
+
+    // leaves the alpha channel, if any, untouched.
+    pub fn fill(&mut self, color: Color) -> &Self {
+        self.pixels.fill(color);
+        self
+    }
+
+    // back to black, and fully transparent if the canvas has alpha.
+    pub fn clear(&mut self) -> &Self {
+        self.pixels.fill(Color::new(0.0, 0.0, 0.0));
+        if let Some(alpha) = &mut self.alpha {
+            alpha.fill(0.0);
+        }
+        self
+    }
+
+    // draws `other` with its top-left corner at (x, y). anything falling
+    // outside this canvas is clipped.
+    pub fn blit(&mut self, other: &Canvas, x: usize, y: usize, blend: Blend) -> &Self {
+        let width = other.width.min(self.width.saturating_sub(x));
+        let height = other.height.min(self.height.saturating_sub(y));
+        if other.alpha.is_some() && self.alpha.is_none() {
+            self.alpha = Some(vec![1.0; self.pixels.len()]);
+        }
+
+        for oy in 0..height {
+            for ox in 0..width {
+                let src = other.pixels[ox + oy * other.width];
+                let src_alpha = other.alpha_at(ox, oy);
+                let i = (x + ox) + (y + oy) * self.width;
+
+                let (color, alpha) = match blend {
+                    Blend::Replace => (src, src_alpha),
+                    Blend::Over => {
+                        let dst_alpha = self.alpha.as_ref().map_or(1.0, |a| a[i]);
+                        let alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+                        if alpha <= 0.0 {
+                            (Color::new(0.0, 0.0, 0.0), 0.0)
+                        } else {
+                            let color = (src * src_alpha
+                                + self.pixels[i] * (dst_alpha * (1.0 - src_alpha)))
+                                * (1.0 / alpha);
+                            (color, alpha)
+                        }
+                    }
+                };
+
+                self.pixels[i] = color;
+                if let Some(a) = &mut self.alpha {
+                    a[i] = alpha;
+                }
+            }
         }
+
+        self
+    }
+
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Result<Canvas> {
+        if x + width > self.width || y + height > self.height {
+            return Err(Error::OutOfBounds {
+                index: (x + width, y + height),
+                size: (self.width, self.height),
+            });
+        }
+
+        let mut canvas = Canvas::from_fn(width, height, |cx, cy| self[(x + cx, y + cy)]);
+        if self.alpha.is_some() {
+            canvas.alpha = Some(
+                (0..width * height)
+                    .map(|i| self.alpha_at(x + i % width, y + i / width))
+                    .collect(),
+            );
+        }
+
+        Ok(canvas)
+    }
+
+    // samples are taken at pixel centers, so both upscaling and downscaling
+    // stay centered on the source image.
+    pub fn resize(&self, width: usize, height: usize, filter: Filter) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        if self.alpha.is_some() {
+            canvas.alpha = Some(vec![0.0; width * height]);
+        }
+        if self.pixels.is_empty() {
+            return canvas;
+        }
+
+        let scale_x = self.width as Float / width as Float;
+        let scale_y = self.height as Float / height as Float;
+
+        for y in 0..height {
+            for x in 0..width {
+                let sx = (x as Float + 0.5) * scale_x - 0.5;
+                let sy = (y as Float + 0.5) * scale_y - 0.5;
+
+                let (color, alpha) = match filter {
+                    Filter::Nearest => {
+                        let nx = (sx.round().max(0.0) as usize).min(self.width - 1);
+                        let ny = (sy.round().max(0.0) as usize).min(self.height - 1);
+                        (self[(nx, ny)], self.alpha_at(nx, ny))
+                    }
+                    Filter::Bilinear => {
+                        let sx = sx.clamp(0.0, (self.width - 1) as Float);
+                        let sy = sy.clamp(0.0, (self.height - 1) as Float);
+                        let (x0, y0) = (sx.floor() as usize, sy.floor() as usize);
+                        let (x1, y1) =
+                            ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                        let (tx, ty) = (sx - x0 as Float, sy - y0 as Float);
+
+                        let top = Color::lerp(self[(x0, y0)], self[(x1, y0)], tx);
+                        let bottom = Color::lerp(self[(x0, y1)], self[(x1, y1)], tx);
+                        let alpha_top = self.alpha_at(x0, y0)
+                            + (self.alpha_at(x1, y0) - self.alpha_at(x0, y0)) * tx;
+                        let alpha_bottom = self.alpha_at(x0, y1)
+                            + (self.alpha_at(x1, y1) - self.alpha_at(x0, y1)) * tx;
+
+                        (
+                            Color::lerp(top, bottom, ty),
+                            alpha_top + (alpha_bottom - alpha_top) * ty,
+                        )
+                    }
+                };
+
+                canvas.pixels[x + y * width] = color;
+                if let Some(a) = &mut canvas.alpha {
+                    a[x + y * width] = alpha;
+                }
+            }
+        }
+
+        canvas
+    }
+
+    // row-major, same order as the `pixels` field.
+    pub fn pixels(&self) -> impl Iterator<Item = &Color> {
+        self.pixels.iter()
+    }
+
+    pub fn pixels_mut(&mut self) -> impl Iterator<Item = &mut Color> {
+        self.pixels.iter_mut()
+    }
+
+    pub fn enumerate_pixels(&self) -> impl Iterator<Item = (usize, usize, &Color)> {
+        let width = self.width;
+        self.pixels
+            .iter()
+            .enumerate()
+            .map(move |(i, p)| (i % width, i / width, p))
+    }
+
+    pub fn enumerate_pixels_mut(&mut self) -> impl Iterator<Item = (usize, usize, &mut Color)> {
+        let width = self.width;
+        self.pixels
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, p)| (i % width, i / width, p))
+    }
+
+    // a canvas with an alpha channel that starts out fully transparent.
+    pub fn new_rgba(width: usize, height: usize) -> Self {
+        Self {
+            alpha: Some(vec![0.0; width * height]),
+            ..Self::new(width, height)
+        }
+    }
+
+    pub fn alpha_at(&self, x: usize, y: usize) -> Float {
+        // goes through Index so bad coordinates panic the same way either way.
+        let _ = self[(x, y)];
+        match &self.alpha {
+            Some(alpha) => alpha[x + y * self.width],
+            None => 1.0,
+        }
+    }
+
+    // adds an opaque alpha channel first if the canvas doesn't have one yet.
+    pub fn write_pixel_rgba(&mut self, x: usize, y: usize, color: Color, alpha: Float) -> &Self {
+        self[(x, y)] = color;
+        let width = self.width;
+        let len = self.pixels.len();
+        self.alpha.get_or_insert_with(|| vec![1.0; len])[x + y * width] = alpha;
+        self
+    }
+
+    // reads a plain (P3) or raw (P6) ppm, including 16-bit raw files. samples are
+    // scaled by maxval into [0, 1].
+    pub fn from_ppm(mut reader: impl Read) -> Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut tokens = PpmTokens {
+            bytes: &bytes,
+            pos: 0,
+            line: 1,
+        };
+
+        let magic = tokens.next_token()?;
+        let binary = match magic.as_str() {
+            "P3" => false,
+            "P6" => true,
+            _ => return Err(tokens.error(format!("unsupported magic number {:?}", magic))),
+        };
+        let width = tokens.next_number()?;
+        let height = tokens.next_number()?;
+        let maxval = tokens.next_number()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(tokens.error(format!("invalid maxval {}", maxval)));
+        }
+
+        // the header is untrusted, so the raster it describes has to fit in the
+        // bytes actually read before anything gets allocated for it.
+        let sample_count = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(3))
+            .ok_or_else(|| tokens.error(format!("image size {}x{} too large", width, height)))?;
+
+        let samples = if binary {
+            // exactly one whitespace byte separates the header from the raster.
+            tokens.pos += 1;
+            let size = if maxval < 256 { 1 } else { 2 };
+            let raster = bytes.get(tokens.pos..).unwrap_or_default();
+            match sample_count.checked_mul(size) {
+                Some(len) if len <= raster.len() => {}
+                _ => return Err(tokens.error("unexpected end of raster data".to_string())),
+            }
+
+            raster
+                .chunks_exact(size)
+                .take(sample_count)
+                .map(|chunk| match chunk {
+                    [v] => *v as usize,
+                    [hi, lo] => u16::from_be_bytes([*hi, *lo]) as usize,
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        } else {
+            // every plain sample takes at least one byte.
+            if sample_count > bytes.len() - tokens.pos {
+                return Err(tokens.error("unexpected end of raster data".to_string()));
+            }
+
+            let mut samples = Vec::with_capacity(sample_count);
+            for _ in 0..sample_count {
+                samples.push(tokens.next_number()?);
+            }
+            samples
+        };
+
+        let mut canvas = Canvas::new(width, height);
+        for (pixel, rgb) in canvas.pixels.iter_mut().zip(samples.chunks_exact(3)) {
+            if let Some(value) = rgb.iter().find(|v| **v > maxval) {
+                return Err(tokens.error(format!("sample {} exceeds maxval {}", value, maxval)));
+            }
+
+            *pixel = Color::new(
+                rgb[0] as Float / maxval as Float,
+                rgb[1] as Float / maxval as Float,
+                rgb[2] as Float / maxval as Float,
+            );
+        }
+
+        Ok(canvas)
     }
 
     pub fn write_pixel(&mut self, x: usize, y: usize, color: Color) -> &Self {
@@ -22,42 +333,470 @@ impl Canvas {
         self
     }
 
-    pub fn write_to_ppm(&self, path: &Path) -> std::io::Result<()> {
-        let mut f = File::create(path)?;
-        let headers = format!("P3\n{} {}\n255\n", self.width, self.height);
-        let mut pixels = String::new();
+    pub fn checked_index(&self, x: usize, y: usize) -> Result<&Color> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds {
+                index: (x, y),
+                size: (self.width, self.height),
+            });
+        }
+
+        Ok(&self.pixels[x + y * self.width])
+    }
+
+    pub fn checked_index_mut(&mut self, x: usize, y: usize) -> Result<&mut Color> {
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds {
+                index: (x, y),
+                size: (self.width, self.height),
+            });
+        }
+
+        Ok(&mut self.pixels[x + y * self.width])
+    }
+
+    // returns a copy with `transfer` applied to every pixel, meant to be called
+    // right before writing the canvas out.
+    pub fn encode(&self, transfer: Transfer) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels: self.pixels.iter().map(|p| p.encode(transfer)).collect(),
+            alpha: self.alpha.clone(),
+        }
+    }
+
+    // applies `exposure` (in stops) and then `tone_map` to every pixel. should
+    // run before `encode`, since tone mapping works on linear values.
+    pub fn tone_map(&self, tone_map: ToneMap, exposure: Float) -> Canvas {
+        Canvas {
+            width: self.width,
+            height: self.height,
+            pixels: self
+                .pixels
+                .iter()
+                .map(|p| p.exposure(exposure).tone_map(tone_map))
+                .collect(),
+            alpha: self.alpha.clone(),
+        }
+    }
 
-        let mut i = 0;
+    pub fn to_ppm(&self) -> String {
+        self.to_ppm_with_maxval(255)
+    }
+
+    pub fn to_ppm_with_maxval(&self, maxval: u16) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = self.write_ppm(&mut buffer, maxval) {
+            // writing to a vec can't fail, so this is always a bad maxval.
+            panic!("{}", e);
+        }
+
+        String::from_utf8(buffer).expect("ppm output is ascii")
+    }
+
+    // streams the image a row at a time and returns the number of bytes
+    // written. maxval above 255 gives 16-bit output. every row starts on a
+    // fresh line and lines are wrapped so none exceed 70 characters, as the
+    // spec requires.
+    pub fn write_ppm(&self, mut writer: impl Write, maxval: u16) -> Result<usize> {
+        if maxval == 0 {
+            return Err(Error::InvalidMaxval(maxval));
+        }
+
+        let header = format!("P3\n{} {}\n{}\n", self.width, self.height, maxval);
+        writer.write_all(header.as_bytes())?;
+        let mut written = header.len();
+
+        let mut row_buffer = String::new();
+        for row in self.pixels.chunks(self.width.max(1)) {
+            row_buffer.clear();
+            let mut line_len = 0;
+
+            for pixel in row {
+                let pixel_int = pixel.to_int(maxval as u32);
+
+                for value in [pixel_int.r, pixel_int.g, pixel_int.b] {
+                    let value = value.to_string();
+
+                    if line_len > 0 && line_len + 1 + value.len() > 70 {
+                        row_buffer.push('\n');
+                        line_len = 0;
+                    }
+
+                    if line_len > 0 {
+                        row_buffer.push(' ');
+                        line_len += 1;
+                    }
+                    row_buffer.push_str(&value);
+                    line_len += value.len();
+                }
+            }
+
+            row_buffer.push('\n');
+            writer.write_all(row_buffer.as_bytes())?;
+            written += row_buffer.len();
+        }
+
+        writer.flush()?;
+        Ok(written)
+    }
+
+    pub fn diff(&self, other: &Canvas) -> DiffReport {
+        if self.width != other.width || self.height != other.height {
+            panic!("cannot compare canvases of different sizes!");
+        }
+
+        let mut squared = 0.0;
+        let errors: Vec<Float> = self
+            .pixels
+            .iter()
+            .zip(other.pixels.iter())
+            .map(|(a, b)| {
+                let d = *a - *b;
+                squared += d.r * d.r + d.g * d.g + d.b * d.b;
+                d.r.abs().max(d.g.abs()).max(d.b.abs())
+            })
+            .collect();
+
+        DiffReport {
+            width: self.width,
+            height: self.height,
+            max_error: errors.iter().cloned().fold(0.0, Float::max),
+            mean_squared_error: squared / (errors.len().max(1) * 3) as Float,
+            errors,
+        }
+    }
+
+    // false for canvases of different sizes rather than panicking like `diff`.
+    pub fn approx_eq(&self, other: &Canvas, tolerance: Float) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.diff(other).max_error <= tolerance
+    }
+
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.pixels.len() * 4);
+
+        for (i, pixel) in self.pixels.iter().enumerate() {
+            let pixel_int = pixel.to_int(255);
+            let alpha = match &self.alpha {
+                Some(alpha) => (alpha[i].clamp(0.0, 1.0) * 255.0).round() as u8,
+                None => 255,
+            };
+            buffer.extend_from_slice(&[
+                pixel_int.r as u8,
+                pixel_int.g as u8,
+                pixel_int.b as u8,
+                alpha,
+            ]);
+        }
+
+        buffer
+    }
+
+    pub fn to_rgb8(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.pixels.len() * 3);
 
         for pixel in self.pixels.iter() {
             let pixel_int = pixel.to_int(255);
-            pixels.push_str(&format!("{} {} {} ", pixel_int.r, pixel_int.g, pixel_int.b));
+            buffer.extend_from_slice(&[pixel_int.r as u8, pixel_int.g as u8, pixel_int.b as u8]);
+        }
+
+        buffer
+    }
+
+    // linear, unclamped floats, e.g. for uploading as a float texture.
+    pub fn to_f32_rgb(&self) -> Vec<f32> {
+        let mut buffer = Vec::with_capacity(self.pixels.len() * 3);
+
+        for pixel in self.pixels.iter() {
+            // a no-op cast with the `f32` feature on.
+            #[allow(clippy::unnecessary_cast)]
+            buffer.extend_from_slice(&[pixel.r as f32, pixel.g as f32, pixel.b as f32]);
+        }
+
+        buffer
+    }
+
+    // renders the canvas with 24-bit ansi colors, two pixels per character
+    // using the upper half block. canvases wider than `max_width` columns are
+    // downscaled first, keeping the aspect ratio.
+    pub fn to_ansi(&self, max_width: usize) -> String {
+        let preview = if self.width > max_width {
+            let height = (self.height * max_width).div_ceil(self.width.max(1));
+            self.resize(max_width, height, Filter::Bilinear)
+        } else {
+            self.clone()
+        };
+
+        let mut out = String::new();
+        for y in (0..preview.height).step_by(2) {
+            for x in 0..preview.width {
+                let top = preview[(x, y)].to_int(255);
+                out.push_str(&format!("\x1b[38;2;{};{};{}m", top.r, top.g, top.b));
+
+                if y + 1 < preview.height {
+                    let bottom = preview[(x, y + 1)].to_int(255);
+                    out.push_str(&format!(
+                        "\x1b[48;2;{};{};{}m",
+                        bottom.r, bottom.g, bottom.b
+                    ));
+                } else {
+                    out.push_str("\x1b[49m");
+                }
+                out.push('\u{2580}');
+            }
+            out.push_str("\x1b[0m\n");
+        }
+
+        out
+    }
+
+    pub fn print_ansi(&self, max_width: usize) {
+        print!("{}", self.to_ansi(max_width));
+    }
+
+    // radiance .hdr with flat (not run-length encoded) rgbe scanlines. values
+    // aren't clamped, so highlights above 1.0 survive.
+    pub fn to_hdr(&self) -> Vec<u8> {
+        let mut buffer = format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )
+        .into_bytes();
+        buffer.reserve(self.pixels.len() * 4);
+
+        for pixel in self.pixels.iter() {
+            buffer.extend_from_slice(&to_rgbe(pixel));
+        }
+
+        buffer
+    }
+
+    pub fn write_to_hdr(&self, path: &Path) -> Result<()> {
+        let mut f = File::create(path)?;
+        f.write_all(&self.to_hdr())?;
+
+        Ok(())
+    }
+
+    // "quite ok image" format, 8-bit rgb, or rgba when the canvas has alpha.
+    pub fn to_qoi(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(14 + self.pixels.len() * 4 + 8);
+        buffer.extend_from_slice(b"qoif");
+        buffer.extend_from_slice(&(self.width as u32).to_be_bytes());
+        buffer.extend_from_slice(&(self.height as u32).to_be_bytes());
+        let channels = if self.alpha.is_some() { 4 } else { 3 };
+        buffer.extend_from_slice(&[channels, 0]);
+
+        // the index starts out zeroed rgba exactly like a decoder's, so both
+        // sides agree on every slot.
+        let mut index = [[0u8; 4]; 64];
+        let mut prev = [0, 0, 0, 255];
+        let mut run = 0u8;
 
-            i += 1;
+        for rgba in self.to_rgba8().chunks_exact(4) {
+            let px = [rgba[0], rgba[1], rgba[2], rgba[3]];
 
-            if i == self.width {
-                i = 0;
-                pixels.push('\n');
+            if px == prev {
+                run += 1;
+                if run == 62 {
+                    buffer.push(0xc0 | (run - 1));
+                    run = 0;
+                }
+                continue;
             }
+
+            if run > 0 {
+                buffer.push(0xc0 | (run - 1));
+                run = 0;
+            }
+
+            let hash = (px[0] as usize * 3
+                + px[1] as usize * 5
+                + px[2] as usize * 7
+                + px[3] as usize * 11)
+                % 64;
+            if index[hash] == px {
+                buffer.push(hash as u8);
+            } else if px[3] != prev[3] {
+                // the diff ops can't change alpha.
+                index[hash] = px;
+                buffer.push(0xff);
+                buffer.extend_from_slice(&px);
+            } else {
+                index[hash] = px;
+
+                let dr = px[0].wrapping_sub(prev[0]) as i8;
+                let dg = px[1].wrapping_sub(prev[1]) as i8;
+                let db = px[2].wrapping_sub(prev[2]) as i8;
+                let dr_dg = dr.wrapping_sub(dg);
+                let db_dg = db.wrapping_sub(dg);
+
+                if (-2..=1).contains(&dr) && (-2..=1).contains(&dg) && (-2..=1).contains(&db) {
+                    buffer.push(
+                        0x40 | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8,
+                    );
+                } else if (-32..=31).contains(&dg)
+                    && (-8..=7).contains(&dr_dg)
+                    && (-8..=7).contains(&db_dg)
+                {
+                    buffer.push(0x80 | (dg + 32) as u8);
+                    buffer.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                } else {
+                    buffer.extend_from_slice(&[0xfe, px[0], px[1], px[2]]);
+                }
+            }
+
+            prev = px;
+        }
+
+        if run > 0 {
+            buffer.push(0xc0 | (run - 1));
         }
 
-        let mut contents = String::new();
-        contents.push_str(&headers);
-        contents.push_str(
-            &pixels
-                .trim()
-                .lines()
-                .map(|part| part.trim())
-                .collect::<Vec<&str>>()
-                .join("\n"),
+        buffer.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        buffer
+    }
+
+    // uncompressed 24-bit truecolor tga with a top-left origin.
+    pub fn to_tga(&self) -> Vec<u8> {
+        assert!(
+            self.width <= u16::MAX as usize && self.height <= u16::MAX as usize,
+            "canvas too large for tga!"
         );
-        contents.push('\n');
 
-        match f.write(contents.as_bytes()) {
-            Ok(_) => Ok(()),
-            Err(e) => panic!("error writing to file: {}", e),
+        let mut buffer = Vec::with_capacity(18 + self.pixels.len() * 3);
+        buffer.extend_from_slice(&[0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buffer.extend_from_slice(&(self.width as u16).to_le_bytes());
+        buffer.extend_from_slice(&(self.height as u16).to_le_bytes());
+        buffer.extend_from_slice(&[24, 0x20]);
+
+        for pixel in self.pixels.iter() {
+            let pixel_int = pixel.to_int(255);
+            buffer.extend_from_slice(&[pixel_int.b as u8, pixel_int.g as u8, pixel_int.r as u8]);
+        }
+
+        buffer
+    }
+
+    pub fn write_to_qoi(&self, path: &Path) -> Result<()> {
+        let mut f = File::create(path)?;
+        f.write_all(&self.to_qoi())?;
+
+        Ok(())
+    }
+
+    pub fn write_to_tga(&self, path: &Path) -> Result<()> {
+        let mut f = File::create(path)?;
+        f.write_all(&self.to_tga())?;
+
+        Ok(())
+    }
+
+    pub fn write_to_ppm(&self, path: &Path) -> Result<usize> {
+        let f = File::create(path)?;
+        self.write_ppm(BufWriter::new(f), 255)
+    }
+}
+
+impl DiffReport {
+    // black where the images match, going through red and yellow to white for
+    // the pixel with the largest error.
+    pub fn heat_map(&self) -> Canvas {
+        let stops = [
+            Color::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ];
+
+        Canvas::from_fn(self.width, self.height, |x, y| {
+            if self.max_error <= 0.0 {
+                return stops[0];
+            }
+
+            let t = self.errors[x + y * self.width] / self.max_error * 3.0;
+            let i = (t.floor() as usize).min(2);
+            Color::lerp(stops[i], stops[i + 1], t - i as Float)
+        })
+    }
+}
+
+// whitespace-separated header (and p3 raster) tokens, skipping `#` comments.
+struct PpmTokens<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+
+impl PpmTokens<'_> {
+    fn error(&self, message: String) -> Error {
+        Error::Parse {
+            line: self.line,
+            message,
         }
     }
+
+    fn next_token(&mut self) -> Result<String> {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == b'#' {
+                while self.bytes.get(self.pos).is_some_and(|b| *b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                if b == b'\n' {
+                    self.line += 1;
+                }
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return Err(self.error("unexpected end of file".to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&self.bytes[start..self.pos]).into_owned())
+    }
+
+    fn next_number(&mut self) -> Result<usize> {
+        let token = self.next_token()?;
+        token
+            .parse()
+            .map_err(|_| self.error(format!("expected a number, got {:?}", token)))
+    }
+}
+
+// shared exponent encoding: the mantissas of all three channels are scaled by
+// the exponent of the brightest one.
+fn to_rgbe(color: &Color) -> [u8; 4] {
+    let (r, g, b) = (color.r.max(0.0), color.g.max(0.0), color.b.max(0.0));
+    let max = r.max(g).max(b);
+    if max < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / (2.0 as Float).powi(exponent);
+
+    [
+        (r * scale).min(255.0) as u8,
+        (g * scale).min(255.0) as u8,
+        (b * scale).min(255.0) as u8,
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
 }
 
 impl std::ops::Index<(usize, usize)> for Canvas {
@@ -89,10 +828,7 @@ impl std::ops::IndexMut<(usize, usize)> for Canvas {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{
-        fs,
-        io::{prelude::*, BufReader},
-    };
+    use std::{fs, io::BufReader};
 
     #[test]
     fn test_canvas() {
@@ -167,4 +903,569 @@ mod tests {
 
         fs::remove_file("test_write_ppm.ppm").unwrap();
     }
+
+    #[test]
+    fn test_to_ppm() {
+        let mut c = Canvas::new(2, 2);
+        c.write_pixel(1, 1, Color::new(1.0, 0.5, 0.0));
+
+        assert_eq!(c.to_ppm(), "P3\n2 2\n255\n0 0 0 0 0 0\n0 0 0 255 128 0\n");
+    }
+
+    #[test]
+    fn test_ppm_line_wrapping() {
+        let mut c = Canvas::new(10, 2);
+        for pixel in c.pixels.iter_mut() {
+            *pixel = Color::new(1.0, 0.8, 0.6);
+        }
+
+        assert_eq!(
+            c.to_ppm(),
+            "P3\n10 2\n255\n\
+             255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204\n\
+             153 255 204 153 255 204 153 255 204 153 255 204 153\n\
+             255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204\n\
+             153 255 204 153 255 204 153 255 204 153 255 204 153\n"
+        );
+        assert!(c.to_ppm().lines().all(|line| line.len() <= 70));
+    }
+
+    #[test]
+    fn test_write_ppm_bytes_written() {
+        let mut c = Canvas::new(12, 3);
+        c.write_pixel(4, 1, Color::new(1.0, 0.5, 0.25));
+
+        let mut buffer = Vec::new();
+        let written = c.write_ppm(&mut buffer, 255).unwrap();
+
+        assert_eq!(written, buffer.len());
+        assert_eq!(String::from_utf8(buffer).unwrap(), c.to_ppm());
+    }
+
+    #[test]
+    fn test_ppm_16_bit() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.5, 0.0));
+
+        assert_eq!(
+            c.to_ppm_with_maxval(65535),
+            "P3\n2 1\n65535\n65535 32768 0 0 0 0\n"
+        );
+    }
+
+    #[test]
+    fn test_write_ppm_zero_maxval() {
+        let mut buffer = Vec::new();
+
+        assert!(matches!(
+            Canvas::new(1, 1).write_ppm(&mut buffer, 0),
+            Err(Error::InvalidMaxval(0))
+        ));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "ppm maxval must be at least 1, got 0")]
+    fn test_to_ppm_zero_maxval() {
+        Canvas::new(1, 1).to_ppm_with_maxval(0);
+    }
+
+    #[test]
+    fn test_to_rgba8() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.5, -1.0));
+
+        assert_eq!(c.to_rgba8(), vec![255, 128, 0, 255, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_encode_canvas() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(0.5, 0.5, 0.5));
+
+        assert_eq!(c.to_ppm(), "P3\n2 1\n255\n128 128 128 0 0 0\n");
+        assert_eq!(
+            c.encode(Transfer::Srgb).to_ppm(),
+            "P3\n2 1\n255\n188 188 188 0 0 0\n"
+        );
+        assert_eq!(c.encode(Transfer::Linear), c);
+    }
+
+    #[test]
+    fn test_tone_map_canvas() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(0.5, 1.5, 4.0));
+
+        let mapped = c.tone_map(ToneMap::Reinhard, 1.0);
+        assert_eq!(mapped[(0, 0)], Color::new(0.5, 0.75, 8.0 / 9.0));
+        assert_eq!(mapped[(1, 0)], Color::new(0.0, 0.0, 0.0));
+        assert_eq!(
+            c.tone_map(ToneMap::Clip, 0.0)[(0, 0)],
+            Color::new(0.5, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_to_hdr() {
+        let mut c = Canvas::new(3, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.5, 0.25));
+        c.write_pixel(1, 0, Color::new(6.0, 0.0, -1.0));
+
+        let hdr = c.to_hdr();
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 3\n";
+
+        assert_eq!(&hdr[..header.len()], header);
+        assert_eq!(
+            &hdr[header.len()..],
+            &[128, 64, 32, 129, 192, 0, 0, 131, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_to_qoi() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let gray = Color::new(0.5, 0.5, 0.5);
+        let black = Color::new(0.0, 0.0, 0.0);
+        let mut c = Canvas::new(6, 1);
+        c.pixels = vec![black, black, red, gray, black, red];
+
+        let mut expected = b"qoif".to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 6, 0, 0, 0, 1, 3, 0]);
+        // run of 2, diff (wrapping), rgb, rgb, index
+        expected.extend_from_slice(&[0xc1, 0x5a, 0xfe, 128, 128, 128, 0xfe, 0, 0, 0, 50]);
+        expected.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+
+        assert_eq!(c.to_qoi(), expected);
+    }
+
+    // straight from the reference decoder in the qoi spec.
+    fn decode_qoi(bytes: &[u8]) -> Vec<[u8; 4]> {
+        let width = u32::from_be_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        let height = u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let mut index = [[0u8; 4]; 64];
+        let mut px = [0, 0, 0, 255];
+        let mut pixels = vec![];
+        let mut pos = 14;
+        let mut run = 0;
+
+        while pixels.len() < width * height {
+            if run > 0 {
+                run -= 1;
+            } else {
+                let b = bytes[pos];
+                pos += 1;
+                match b {
+                    0xfe => {
+                        px[..3].copy_from_slice(&bytes[pos..pos + 3]);
+                        pos += 3;
+                    }
+                    0xff => {
+                        px.copy_from_slice(&bytes[pos..pos + 4]);
+                        pos += 4;
+                    }
+                    _ => match b >> 6 {
+                        0 => px = index[b as usize],
+                        1 => {
+                            px[0] = px[0].wrapping_add((b >> 4 & 3).wrapping_sub(2));
+                            px[1] = px[1].wrapping_add((b >> 2 & 3).wrapping_sub(2));
+                            px[2] = px[2].wrapping_add((b & 3).wrapping_sub(2));
+                        }
+                        2 => {
+                            let dg = (b & 0x3f).wrapping_sub(32);
+                            let next = bytes[pos];
+                            pos += 1;
+                            px[0] = px[0].wrapping_add(dg.wrapping_add(next >> 4).wrapping_sub(8));
+                            px[1] = px[1].wrapping_add(dg);
+                            px[2] =
+                                px[2].wrapping_add(dg.wrapping_add(next & 0x0f).wrapping_sub(8));
+                        }
+                        _ => run = b & 0x3f,
+                    },
+                }
+            }
+
+            let hash = (px[0] as usize * 3
+                + px[1] as usize * 5
+                + px[2] as usize * 7
+                + px[3] as usize * 11)
+                % 64;
+            index[hash] = px;
+            pixels.push(px);
+        }
+
+        pixels
+    }
+
+    #[test]
+    fn test_qoi_round_trip() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let green = Color::new(0.0, 1.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let black = Color::new(0.0, 0.0, 0.0);
+
+        // black hashes to the slot an rgb-only index would see as already
+        // holding black.
+        let mut c = Canvas::new(5, 1);
+        c.pixels = vec![white, black, red, green, red];
+        assert_eq!(
+            decode_qoi(&c.to_qoi()),
+            vec![
+                [255, 255, 255, 255],
+                [0, 0, 0, 255],
+                [255, 0, 0, 255],
+                [0, 255, 0, 255],
+                [255, 0, 0, 255],
+            ]
+        );
+
+        let c = Canvas::from_fn(16, 16, |x, y| {
+            Color::new(
+                ((x * 37 + y * 11) % 7) as Float / 6.0,
+                (x % 3) as Float / 2.0,
+                ((x ^ y) % 5) as Float / 4.0,
+            )
+        });
+        let expected: Vec<[u8; 4]> = c
+            .to_rgba8()
+            .chunks(4)
+            .map(|p| p.try_into().unwrap())
+            .collect();
+        assert_eq!(decode_qoi(&c.to_qoi()), expected);
+    }
+
+    #[test]
+    fn test_qoi_alpha() {
+        let mut c = Canvas::new_rgba(4, 4);
+        for (i, (x, y)) in (0..4).flat_map(|y| (0..4).map(move |x| (x, y))).enumerate() {
+            let color = Color::new(x as Float / 3.0, y as Float / 3.0, 0.5);
+            c.write_pixel_rgba(x, y, color, (i % 3) as Float / 2.0);
+        }
+        let qoi = c.to_qoi();
+
+        assert_eq!(qoi[12], 4);
+        let expected: Vec<[u8; 4]> = c
+            .to_rgba8()
+            .chunks(4)
+            .map(|p| p.try_into().unwrap())
+            .collect();
+        assert_eq!(decode_qoi(&qoi), expected);
+        // opaque canvases still say rgb.
+        assert_eq!(Canvas::new(1, 1).to_qoi()[12], 3);
+    }
+
+    #[test]
+    fn test_to_tga() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        c.write_pixel(1, 0, Color::new(0.5, 0.5, 0.5));
+
+        assert_eq!(
+            c.to_tga(),
+            vec![
+                0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0, 24, 0x20, 0, 0, 255, 128, 128, 128
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_ppm_plain() {
+        let ppm = "P3\n# a comment\n2 1 # trailing comment\n255\n255 0 0\n  0 51\t255\n";
+        let c = Canvas::from_ppm(ppm.as_bytes()).unwrap();
+
+        assert_eq!((c.width, c.height), (2, 1));
+        assert_eq!(c[(0, 0)], Color::new(1.0, 0.0, 0.0));
+        assert_eq!(c[(1, 0)], Color::new(0.0, 0.2, 1.0));
+    }
+
+    #[test]
+    fn test_from_ppm_roundtrip() {
+        let mut c = Canvas::new(3, 2);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 1.0));
+        c.write_pixel(2, 1, Color::new(0.0, 1.0, 0.0));
+
+        assert_eq!(Canvas::from_ppm(c.to_ppm().as_bytes()).unwrap(), c);
+    }
+
+    #[test]
+    fn test_from_ppm_raw() {
+        let mut ppm = b"P6 1 2\n#comment\n255\n".to_vec();
+        ppm.extend_from_slice(&[255, 0, 51, 0, 255, 0]);
+        let c = Canvas::from_ppm(&ppm[..]).unwrap();
+
+        assert_eq!(c[(0, 0)], Color::new(1.0, 0.0, 0.2));
+        assert_eq!(c[(0, 1)], Color::new(0.0, 1.0, 0.0));
+
+        let mut ppm = b"P6 1 1 65535 ".to_vec();
+        ppm.extend_from_slice(&[0xff, 0xff, 0, 0, 0x7f, 0xff]);
+        let c = Canvas::from_ppm(&ppm[..]).unwrap();
+
+        assert_eq!(c[(0, 0)], Color::new(1.0, 0.0, 32767.0 / 65535.0));
+    }
+
+    #[test]
+    fn test_from_ppm_errors() {
+        assert!(matches!(
+            Canvas::from_ppm("P5\n1 1\n255\n0".as_bytes()),
+            Err(Error::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            Canvas::from_ppm("P3\n1 1\n255\n0 0\n".as_bytes()),
+            Err(Error::Parse { line: 5, .. })
+        ));
+        assert!(matches!(
+            Canvas::from_ppm("P3\n1 1\n255\n0 x 0\n".as_bytes()),
+            Err(Error::Parse { line: 4, .. })
+        ));
+        assert!(Canvas::from_ppm("P3 1 1 15 0 16 0".as_bytes()).is_err());
+        assert!(Canvas::from_ppm(&b"P6 2 1 255 \x00\x00\x00"[..]).is_err());
+
+        // huge headers are rejected before anything is allocated for them.
+        for header in [
+            "P6 4000000000 4000000000 255 ",
+            "P6 4000000000 4000000000 65535 ",
+            "P3 4000000000 4000000000 255 ",
+            "P6 100000 100000 255 \0\0\0",
+            "P3 100000 100000 255 0 0 0",
+        ] {
+            assert!(matches!(
+                Canvas::from_ppm(header.as_bytes()),
+                Err(Error::Parse { line: 1, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_canvas_alpha() {
+        let mut c = Canvas::new(2, 1);
+        assert_eq!(c.alpha_at(1, 0), 1.0);
+
+        c.write_pixel_rgba(0, 0, Color::new(1.0, 0.0, 0.0), 0.5);
+        assert_eq!(c.alpha_at(0, 0), 0.5);
+        assert_eq!(c.alpha_at(1, 0), 1.0);
+        assert_eq!(c.to_rgba8(), vec![255, 0, 0, 128, 0, 0, 0, 255]);
+
+        let c = Canvas::new_rgba(2, 1);
+        assert_eq!(c.to_rgba8(), vec![0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(c.encode(Transfer::Srgb).alpha, c.alpha);
+    }
+
+    #[test]
+    fn test_from_fn_canvas() {
+        let c = Canvas::from_fn(3, 2, |x, y| Color::new(x as Float, y as Float, 0.0));
+
+        assert_eq!(c[(0, 0)], Color::new(0.0, 0.0, 0.0));
+        assert_eq!(c[(2, 0)], Color::new(2.0, 0.0, 0.0));
+        assert_eq!(c[(1, 1)], Color::new(1.0, 1.0, 0.0));
+        assert_eq!(Canvas::from_fn(0, 4, |_, _| unreachable!()).pixels.len(), 0);
+    }
+
+    #[test]
+    fn test_fill_clear_canvas() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let mut c = Canvas::new_rgba(2, 2);
+        c.write_pixel_rgba(1, 1, red, 1.0);
+
+        c.fill(red);
+        assert!(c.pixels.iter().all(|p| *p == red));
+        assert_eq!(c.alpha_at(1, 1), 1.0);
+
+        c.clear();
+        assert_eq!(c, Canvas::new_rgba(2, 2));
+    }
+
+    #[test]
+    fn test_blit_replace() {
+        let red = Color::new(1.0, 0.0, 0.0);
+        let mut c = Canvas::new(3, 3);
+        let mut tile = Canvas::new(2, 2);
+        tile.fill(red);
+
+        c.blit(&tile, 2, 1, Blend::Replace);
+
+        assert_eq!(c[(2, 1)], red);
+        assert_eq!(c[(2, 2)], red);
+        assert_eq!(c[(1, 1)], Color::new(0.0, 0.0, 0.0));
+        assert_eq!(c.pixels.iter().filter(|p| **p == red).count(), 2);
+        assert!(c.alpha.is_none());
+
+        c.blit(&tile, 5, 5, Blend::Replace);
+        assert_eq!(c.pixels.iter().filter(|p| **p == red).count(), 2);
+    }
+
+    #[test]
+    fn test_blit_over() {
+        let mut c = Canvas::new(2, 1);
+        c.fill(Color::new(0.0, 0.0, 1.0));
+        let mut overlay = Canvas::new_rgba(2, 1);
+        overlay.write_pixel_rgba(0, 0, Color::new(1.0, 0.0, 0.0), 0.25);
+
+        c.blit(&overlay, 0, 0, Blend::Over);
+
+        assert_eq!(c[(0, 0)], Color::new(0.25, 0.0, 0.75));
+        assert_eq!(c[(1, 0)], Color::new(0.0, 0.0, 1.0));
+        assert_eq!(c.alpha_at(0, 0), 1.0);
+
+        let mut empty = Canvas::new_rgba(1, 1);
+        empty.blit(&overlay, 0, 0, Blend::Over);
+        assert_eq!(empty[(0, 0)], Color::new(1.0, 0.0, 0.0));
+        assert_eq!(empty.alpha_at(0, 0), 0.25);
+    }
+
+    #[test]
+    fn test_crop() {
+        let c = Canvas::from_fn(4, 3, |x, y| Color::new(x as Float, y as Float, 0.0));
+        let cropped = c.crop(1, 1, 2, 2).unwrap();
+
+        assert_eq!((cropped.width, cropped.height), (2, 2));
+        assert_eq!(cropped[(0, 0)], c[(1, 1)]);
+        assert_eq!(cropped[(1, 1)], c[(2, 2)]);
+        assert_eq!(c.crop(0, 0, 4, 3).unwrap(), c);
+        assert!(matches!(
+            c.crop(3, 0, 2, 1),
+            Err(Error::OutOfBounds {
+                index: (5, 1),
+                size: (4, 3)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_resize_nearest() {
+        let c = Canvas::from_fn(2, 2, |x, y| Color::new(x as Float, y as Float, 0.0));
+        let up = c.resize(4, 4, Filter::Nearest);
+
+        assert_eq!(up[(0, 0)], c[(0, 0)]);
+        assert_eq!(up[(1, 1)], c[(0, 0)]);
+        assert_eq!(up[(2, 1)], c[(1, 0)]);
+        assert_eq!(up[(3, 3)], c[(1, 1)]);
+        assert_eq!(up.resize(2, 2, Filter::Nearest), c);
+    }
+
+    #[test]
+    fn test_resize_bilinear() {
+        let c = Canvas::from_fn(2, 1, |x, _| Color::new(x as Float, 0.0, 0.0));
+        let up = c.resize(4, 1, Filter::Bilinear);
+
+        assert_eq!(up[(0, 0)], Color::new(0.0, 0.0, 0.0));
+        assert_eq!(up[(1, 0)], Color::new(0.25, 0.0, 0.0));
+        assert_eq!(up[(2, 0)], Color::new(0.75, 0.0, 0.0));
+        assert_eq!(up[(3, 0)], Color::new(1.0, 0.0, 0.0));
+
+        let down = c.resize(1, 1, Filter::Bilinear);
+        assert_eq!(down[(0, 0)], Color::new(0.5, 0.0, 0.0));
+        assert!(Canvas::new_rgba(3, 3)
+            .resize(1, 1, Filter::Bilinear)
+            .alpha
+            .is_some());
+    }
+
+    #[test]
+    fn test_pixel_iterators() {
+        let mut c = Canvas::new(3, 2);
+
+        for (x, y, pixel) in c.enumerate_pixels_mut() {
+            *pixel = Color::new(x as Float, y as Float, 0.0);
+        }
+        for pixel in c.pixels_mut() {
+            pixel.b = 1.0;
+        }
+
+        assert_eq!(c[(2, 1)], Color::new(2.0, 1.0, 1.0));
+        assert_eq!(c.pixels().count(), 6);
+        assert!(c
+            .enumerate_pixels()
+            .all(|(x, y, p)| *p == Color::new(x as Float, y as Float, 1.0)));
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Canvas::new(2, 2);
+        let mut b = a.clone();
+        b.write_pixel(1, 0, Color::new(0.5, 0.25, 0.0));
+
+        let report = a.diff(&b);
+        assert_eq!(report.max_error, 0.5);
+        assert_eq!(report.mean_squared_error, (0.25 + 0.0625) / 12.0);
+        assert_eq!(report.errors, vec![0.0, 0.5, 0.0, 0.0]);
+
+        let heat_map = report.heat_map();
+        assert_eq!(heat_map[(0, 0)], Color::new(0.0, 0.0, 0.0));
+        assert_eq!(heat_map[(1, 0)], Color::new(1.0, 1.0, 1.0));
+
+        assert_eq!(a.diff(&a).heat_map(), Canvas::new(2, 2));
+    }
+
+    #[test]
+    fn test_approx_eq_canvas() {
+        let a = Canvas::new(2, 1);
+        let mut b = a.clone();
+        b.write_pixel(0, 0, Color::new(0.001, 0.0, 0.0));
+
+        assert!(a.approx_eq(&b, 0.01));
+        assert!(!a.approx_eq(&b, 0.0001));
+        assert!(!a.approx_eq(&Canvas::new(1, 2), 1.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot compare canvases of different sizes!")]
+    fn test_diff_size_mismatch() {
+        Canvas::new(2, 1).diff(&Canvas::new(1, 2));
+    }
+
+    #[test]
+    fn test_to_rgb8_f32_rgb() {
+        let mut c = Canvas::new(2, 1);
+        c.write_pixel(0, 0, Color::new(1.0, 0.5, -1.0));
+        c.write_pixel(1, 0, Color::new(2.0, 0.25, 0.0));
+
+        assert_eq!(c.to_rgb8(), vec![255, 128, 0, 255, 64, 0]);
+        assert_eq!(c.to_f32_rgb(), vec![1.0, 0.5, -1.0, 2.0, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn test_to_ansi() {
+        let mut c = Canvas::new(2, 3);
+        c.write_pixel(0, 0, Color::new(1.0, 0.0, 0.0));
+        c.write_pixel(0, 1, Color::new(0.0, 0.0, 1.0));
+
+        let ansi = c.to_ansi(80);
+        let lines: Vec<&str> = ansi.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}"));
+        assert!(lines[1].contains("\x1b[49m"));
+        assert!(lines.iter().all(|line| line.ends_with("\x1b[0m")));
+
+        let wide = Canvas::new(100, 50).to_ansi(20);
+        assert_eq!(wide.lines().count(), 5);
+        assert_eq!(wide.lines().next().unwrap().matches('\u{2580}').count(), 20);
+    }
+
+    #[test]
+    fn test_checked_index_canvas() {
+        let mut c = Canvas::new(3, 2);
+        let red = Color::new(1.0, 0.0, 0.0);
+
+        *c.checked_index_mut(2, 1).unwrap() = red;
+
+        assert_eq!(c.checked_index(2, 1).unwrap(), &red);
+        assert!(matches!(
+            c.checked_index(3, 0),
+            Err(Error::OutOfBounds {
+                index: (3, 0),
+                size: (3, 2)
+            })
+        ));
+        assert!(c.checked_index_mut(0, 2).is_err());
+    }
+
+    #[test]
+    fn test_write_ppm_io_error() {
+        let c = Canvas::new(1, 1);
+
+        assert!(matches!(
+            c.write_to_ppm(Path::new("does/not/exist/test.ppm")),
+            Err(Error::Io(_))
+        ));
+    }
 }
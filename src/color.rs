@@ -1,6 +1,10 @@
+use crate::{
+    approx,
+    error::{Error, Result},
+    Float,
+};
 use float_eq::{derive_float_eq, float_eq};
 use num_traits::identities::Zero;
-use std::f64::{self, EPSILON};
 use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
 
 #[derive_float_eq(
@@ -8,20 +12,161 @@ use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
     ulps_tol_derive = "Clone, Copy, Debug, PartialEq",
     debug_ulps_diff = "ColorDebugUlpsDiff",
     debug_ulps_diff_derive = "Clone, Copy, Debug, PartialEq",
-    all_tol = "f64"
+    all_tol = "Float"
 )]
 #[derive(Clone, Copy, Debug)]
 pub struct Color {
-    pub r: f64,
-    pub g: f64,
-    pub b: f64,
+    pub r: Float,
+    pub g: Float,
+    pub b: Float,
 }
 
 impl Color {
-    pub fn new(r: f64, g: f64, b: f64) -> Self {
+    pub fn new(r: Float, g: Float, b: Float) -> Self {
         Self { r, g, b }
     }
 
+    // accepts "#rrggbb" or the "#rgb" shorthand, with or without the '#'.
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let invalid = || Error::InvalidColor(hex.to_string());
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        // from_str_radix also takes a leading sign, which isn't a hex digit.
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        let channel = |s: &str| u8::from_str_radix(s, 16).map_err(|_| invalid());
+        let (r, g, b) = match digits.len() {
+            6 => (
+                channel(&digits[0..2])?,
+                channel(&digits[2..4])?,
+                channel(&digits[4..6])?,
+            ),
+            3 => (
+                channel(&digits[0..1])? * 17,
+                channel(&digits[1..2])? * 17,
+                channel(&digits[2..3])? * 17,
+            ),
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self::new(
+            r as Float / 255.0,
+            g as Float / 255.0,
+            b as Float / 255.0,
+        ))
+    }
+
+    // the sixteen basic css color keywords plus orange, case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let hex = match name.to_ascii_lowercase().as_str() {
+            "black" => "000000",
+            "silver" => "c0c0c0",
+            "gray" | "grey" => "808080",
+            "white" => "ffffff",
+            "maroon" => "800000",
+            "red" => "ff0000",
+            "purple" => "800080",
+            "fuchsia" | "magenta" => "ff00ff",
+            "green" => "008000",
+            "lime" => "00ff00",
+            "olive" => "808000",
+            "yellow" => "ffff00",
+            "navy" => "000080",
+            "blue" => "0000ff",
+            "teal" => "008080",
+            "aqua" | "cyan" => "00ffff",
+            "orange" => "ffa500",
+            _ => return None,
+        };
+
+        Self::from_hex(hex).ok()
+    }
+
+    // approximate color of a blackbody at `kelvin`, using tanner helland's
+    // curve fit. the fit is only meant for 1000k to 40000k, so the input is
+    // clamped to that range.
+    pub fn from_kelvin(kelvin: Float) -> Self {
+        let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+
+        let r = if t <= 66.0 {
+            255.0
+        } else {
+            329.69873 * (t - 60.0).powf(-0.13320476)
+        };
+        let g = if t <= 66.0 {
+            99.4708 * t.ln() - 161.11957
+        } else {
+            288.12217 * (t - 60.0).powf(-0.075514849)
+        };
+        let b = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.51773 * (t - 10.0).ln() - 305.0448
+        };
+
+        Self::new(
+            r.clamp(0.0, 255.0) / 255.0,
+            g.clamp(0.0, 255.0) / 255.0,
+            b.clamp(0.0, 255.0) / 255.0,
+        )
+    }
+
+    pub fn luminance(&self) -> Float {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn encode(&self, transfer: Transfer) -> Self {
+        Self {
+            r: transfer.encode(self.r),
+            g: transfer.encode(self.g),
+            b: transfer.encode(self.b),
+        }
+    }
+
+    // scales by 2^ev, so +1 doubles the brightness.
+    pub fn exposure(&self, ev: Float) -> Self {
+        *self * (2.0 as Float).powf(ev)
+    }
+
+    pub fn tone_map(&self, tone_map: ToneMap) -> Self {
+        Self {
+            r: tone_map.map(self.r),
+            g: tone_map.map(self.g),
+            b: tone_map.map(self.b),
+        }
+    }
+
+    pub fn lerp(a: Color, b: Color, t: Float) -> Self {
+        a + (b - a) * t
+    }
+
+    pub fn clamp(&self, min: Float, max: Float) -> Self {
+        Self {
+            r: self.r.clamp(min, max),
+            g: self.g.clamp(min, max),
+            b: self.b.clamp(min, max),
+        }
+    }
+
+    pub fn min(&self, other: &Color) -> Self {
+        Self {
+            r: self.r.min(other.r),
+            g: self.g.min(other.g),
+            b: self.b.min(other.b),
+        }
+    }
+
+    pub fn max(&self, other: &Color) -> Self {
+        Self {
+            r: self.r.max(other.r),
+            g: self.g.max(other.g),
+            b: self.b.max(other.b),
+        }
+    }
+
     pub fn to_int(self, max: u32) -> ColorInt {
         let r: u32;
         let g: u32;
@@ -32,7 +177,7 @@ impl Color {
         } else if self.r < 0.0 {
             r = 0;
         } else {
-            r = (self.r * max as f64).ceil() as u32;
+            r = (self.r * max as Float).ceil() as u32;
         }
 
         if self.g >= 1.0 {
@@ -40,7 +185,7 @@ impl Color {
         } else if self.g < 0.0 {
             g = 0;
         } else {
-            g = (self.g * max as f64).ceil() as u32;
+            g = (self.g * max as Float).ceil() as u32;
         }
 
         if self.b >= 1.0 {
@@ -48,7 +193,7 @@ impl Color {
         } else if self.b < 0.0 {
             b = 0;
         } else {
-            b = (self.b * max as f64).ceil() as u32;
+            b = (self.b * max as Float).ceil() as u32;
         }
 
         ColorInt { r, g, b }
@@ -57,13 +202,7 @@ impl Color {
 
 impl PartialEq for Color {
     fn eq(&self, other: &Self) -> bool {
-        let cmp = Color {
-            r: 1.0 * EPSILON,
-            g: 1.0 * EPSILON,
-            b: 1.0 * EPSILON,
-        };
-
-        float_eq!(self, other, abs <= cmp)
+        float_eq!(self, other, abs_all <= approx::epsilon())
     }
 }
 
@@ -125,10 +264,10 @@ impl Neg for Color {
     }
 }
 
-impl Mul<f64> for Color {
+impl Mul<Float> for Color {
     type Output = Self;
 
-    fn mul(self, other: f64) -> Self {
+    fn mul(self, other: Float) -> Self {
         Self {
             r: self.r * other,
             g: self.g * other,
@@ -163,6 +302,55 @@ impl Zero for Color {
     }
 }
 
+// transfer function applied to linear colors before quantizing them for
+// output. `Linear` leaves values as they are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transfer {
+    Linear,
+    Gamma(Float),
+    Srgb,
+}
+
+impl Transfer {
+    pub fn encode(&self, value: Float) -> Float {
+        let value = value.max(0.0);
+        match self {
+            Transfer::Linear => value,
+            Transfer::Gamma(gamma) => value.powf(1.0 / gamma),
+            Transfer::Srgb => {
+                if value <= 0.0031308 {
+                    value * 12.92
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                }
+            }
+        }
+    }
+}
+
+// operators compressing hdr values into [0, 1) ahead of the transfer function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMap {
+    Clip,
+    Reinhard,
+    // narkowicz's fit of the aces filmic curve.
+    Aces,
+}
+
+impl ToneMap {
+    pub fn map(&self, value: Float) -> Float {
+        let value = value.max(0.0);
+        match self {
+            ToneMap::Clip => value.min(1.0),
+            ToneMap::Reinhard => value / (1.0 + value),
+            ToneMap::Aces => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                ((value * (a * value + b)) / (value * (c * value + d) + e)).clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct ColorInt {
     pub r: u32,
@@ -173,6 +361,7 @@ pub struct ColorInt {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use float_eq::assert_float_eq;
 
     #[test]
     fn test_color() {
@@ -218,4 +407,123 @@ mod tests {
 
         assert_eq!(a * b, result);
     }
+
+    #[test]
+    fn test_luminance() {
+        assert_eq!(Color::new(1.0, 1.0, 1.0).luminance(), 1.0);
+        assert_eq!(Color::new(0.0, 1.0, 0.0).luminance(), 0.7152);
+    }
+
+    #[test]
+    fn test_lerp_color() {
+        let a = Color::new(0.0, 0.5, 1.0);
+        let b = Color::new(1.0, 0.5, 0.0);
+
+        assert_eq!(Color::lerp(a, b, 0.5), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(Color::lerp(a, b, 1.0), b);
+    }
+
+    #[test]
+    fn test_clamp_min_max_color() {
+        let a = Color::new(-0.5, 0.4, 1.7);
+        let b = Color::new(0.0, 0.2, 2.0);
+
+        assert_eq!(a.clamp(0.0, 1.0), Color::new(0.0, 0.4, 1.0));
+        assert_eq!(a.min(&b), Color::new(-0.5, 0.2, 1.7));
+        assert_eq!(a.max(&b), Color::new(0.0, 0.4, 2.0));
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(
+            Color::from_hex("#ff8800").unwrap(),
+            Color::new(1.0, 136.0 / 255.0, 0.0)
+        );
+        assert_eq!(
+            Color::from_hex("F80").unwrap(),
+            Color::from_hex("#ff8800").unwrap()
+        );
+        assert!(matches!(
+            Color::from_hex("#ff88"),
+            Err(Error::InvalidColor(_))
+        ));
+        assert!(matches!(
+            Color::from_hex("#gg0000"),
+            Err(Error::InvalidColor(_))
+        ));
+        assert!(Color::from_hex("#ff€0").is_err());
+        assert!(matches!(
+            Color::from_hex("#+f+f+f"),
+            Err(Error::InvalidColor(_))
+        ));
+        assert!(Color::from_hex("+f0").is_err());
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Color::from_name("White"), Some(Color::new(1.0, 1.0, 1.0)));
+        assert_eq!(Color::from_name("navy"), Color::from_hex("#000080").ok());
+        assert_eq!(Color::from_name("not a color"), None);
+    }
+
+    #[test]
+    fn test_encode_srgb() {
+        let c = Color::new(0.0, 0.002, 0.5).encode(Transfer::Srgb);
+
+        assert_eq!(c.r, 0.0);
+        assert_float_eq!(c.g, 0.02584, abs <= 1e-5);
+        assert_float_eq!(c.b, 0.73536, abs <= 1e-5);
+        assert_eq!(
+            Color::new(1.0, 1.0, 1.0).encode(Transfer::Srgb),
+            Color::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_encode_gamma() {
+        let c = Color::new(0.25, -1.0, 0.5);
+
+        assert_eq!(
+            c.encode(Transfer::Gamma(2.0)),
+            Color::new(0.5, 0.0, (0.5 as Float).sqrt())
+        );
+        assert_eq!(c.encode(Transfer::Linear), Color::new(0.25, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_exposure() {
+        let c = Color::new(0.25, 0.5, 1.0);
+
+        assert_eq!(c.exposure(1.0), Color::new(0.5, 1.0, 2.0));
+        assert_eq!(c.exposure(-2.0), Color::new(0.0625, 0.125, 0.25));
+        assert_eq!(c.exposure(0.0), c);
+    }
+
+    #[test]
+    fn test_tone_map() {
+        let c = Color::new(1.0, 3.0, -1.0);
+
+        assert_eq!(c.tone_map(ToneMap::Clip), Color::new(1.0, 1.0, 0.0));
+        assert_eq!(c.tone_map(ToneMap::Reinhard), Color::new(0.5, 0.75, 0.0));
+
+        let aces = c.tone_map(ToneMap::Aces);
+        assert_float_eq!(aces.r, 0.80379, abs <= 1e-5);
+        assert!(aces.g > aces.r && aces.g < 1.0);
+        assert_eq!(aces.b, 0.0);
+    }
+
+    #[test]
+    fn test_from_kelvin() {
+        let tungsten = Color::from_kelvin(3200.0);
+        let daylight = Color::from_kelvin(6500.0);
+        let sky = Color::from_kelvin(15000.0);
+
+        assert_eq!(tungsten.r, 1.0);
+        assert!(tungsten.g > tungsten.b);
+        assert_float_eq!(daylight.r, 1.0, abs <= 1e-5);
+        assert_float_eq!(daylight.g, 254.11 / 255.0, abs <= 1e-3);
+        assert_float_eq!(daylight.b, 250.04 / 255.0, abs <= 1e-3);
+        assert!(sky.b == 1.0 && sky.r < sky.b);
+        assert_eq!(Color::from_kelvin(500.0), Color::from_kelvin(1000.0));
+    }
 }
@@ -0,0 +1,119 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    DimensionMismatch {
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    NonSquareMatrix {
+        width: usize,
+        height: usize,
+    },
+    SingularMatrix,
+    InvalidDataLength {
+        expected: usize,
+        found: usize,
+    },
+    OutOfBounds {
+        index: (usize, usize),
+        size: (usize, usize),
+    },
+    Io(io::Error),
+    Parse {
+        line: usize,
+        message: String,
+    },
+    InvalidColor(String),
+    UnsupportedFormat(String),
+    InvalidMaxval(u16),
+    EmptyImage,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DimensionMismatch { left, right } => write!(
+                f,
+                "dimension mismatch between ({} {}) and ({} {})",
+                left.0, left.1, right.0, right.1
+            ),
+            Error::NonSquareMatrix { width, height } => write!(
+                f,
+                "expected a square matrix, got size ({} {})",
+                width, height
+            ),
+            Error::SingularMatrix => write!(f, "cannot invert matrices with determinant of 0"),
+            Error::InvalidDataLength { expected, found } => write!(
+                f,
+                "expected {} values for matrix data, got {}",
+                expected, found
+            ),
+            Error::OutOfBounds { index, size } => write!(
+                f,
+                "out of bounds! tried to get index of ({}, {}) for size ({}, {})",
+                index.0, index.1, size.0, size.1
+            ),
+            Error::Io(e) => write!(f, "i/o error: {}", e),
+            Error::Parse { line, message } => {
+                write!(f, "parse error on line {}: {}", line, message)
+            }
+            Error::InvalidColor(s) => write!(f, "invalid color: {:?}", s),
+            Error::UnsupportedFormat(s) => write!(f, "unsupported image format: {:?}", s),
+            Error::InvalidMaxval(v) => write!(f, "ppm maxval must be at least 1, got {}", v),
+            Error::EmptyImage => write!(f, "image has no pixels"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_error() {
+        let e = Error::DimensionMismatch {
+            left: (2, 2),
+            right: (3, 2),
+        };
+
+        assert_eq!(e.to_string(), "dimension mismatch between (2 2) and (3 2)");
+        assert_eq!(
+            Error::SingularMatrix.to_string(),
+            "cannot invert matrices with determinant of 0"
+        );
+    }
+
+    #[test]
+    fn test_io_error() {
+        let e = Error::from(io::Error::new(io::ErrorKind::NotFound, "missing.ppm"));
+
+        assert_eq!(e.to_string(), "i/o error: missing.ppm");
+        assert!(std::error::Error::source(&e).is_some());
+        assert_eq!(
+            Error::Parse {
+                line: 3,
+                message: "expected a number".to_string()
+            }
+            .to_string(),
+            "parse error on line 3: expected a number"
+        );
+    }
+}
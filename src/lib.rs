@@ -0,0 +1,31 @@
+pub mod accumulation;
+pub mod animation;
+pub mod approx;
+pub mod canvas;
+pub mod color;
+pub mod error;
+pub mod matrix;
+pub mod mesh;
+pub mod qc;
+pub mod sampling;
+mod simd;
+pub mod sky;
+pub mod texture;
+pub mod transformation;
+pub mod tuple;
+
+#[cfg(not(feature = "f32"))]
+mod float {
+    pub type Float = f64;
+    pub use std::f64::consts;
+}
+
+#[cfg(feature = "f32")]
+mod float {
+    pub type Float = f32;
+    pub use std::f32::consts;
+}
+
+pub use float::{consts, Float};
+
+pub use error::{Error, Result};
@@ -1,23 +1,17 @@
-use transformation::rotation_y;
-
-use crate::{canvas::Canvas, color::Color, tuple::Tuple};
-use std::{f64::consts::PI, fs, path::Path, process::Command};
-
-mod canvas;
-mod color;
-mod matrix;
-mod transformation;
-mod tuple;
+use renachan::{
+    canvas::Canvas, color::Color, consts::PI, transformation::rotation_y, tuple::Tuple, Float,
+};
+use std::{fs, path::Path, process::Command};
 
 fn main() {
     let mut c = Canvas::new(900, 900);
-    let radius = (3.0 / 8.0) * c.width as f64;
+    let radius = (3.0 / 8.0) * c.width as Float;
 
-    let center = Tuple::point(c.width as f64 / 2.0, 0.0, c.height as f64 / 2.0);
+    let center = Tuple::point(c.width as Float / 2.0, 0.0, c.height as Float / 2.0);
     let twelve = Tuple::point(0.0, 0.0, 1.0);
 
     for i in 1..12 {
-        let r = rotation_y(i as f64 * PI / 6.0);
+        let r = rotation_y(i as Float * PI / 6.0);
         let mut point = r * twelve;
         point.x = point.x * radius + center.x;
         point.z = point.z * radius + center.z;
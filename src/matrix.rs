@@ -1,20 +1,49 @@
-use crate::tuple::Tuple;
+use crate::{
+    approx,
+    error::{Error, Result},
+    simd,
+    tuple::{Point, Tuple, Vector},
+    Float,
+};
 use float_eq::float_eq;
-use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Mul, MulAssign, Sub};
 
 #[derive(Clone, Debug)]
 pub struct Matrix {
     pub width: usize,
     pub height: usize,
-    pub data: Vec<f64>,
+    pub data: Vec<Float>,
 }
 
 impl Matrix {
-    pub fn new(width: usize, height: usize, data: Vec<f64>) -> Self {
-        Self {
+    pub fn new(width: usize, height: usize, data: Vec<Float>) -> Self {
+        match Self::try_new(width, height, data) {
+            Ok(matrix) => matrix,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    pub fn try_new(width: usize, height: usize, data: Vec<Float>) -> Result<Self> {
+        if data.len() != width * height {
+            return Err(Error::InvalidDataLength {
+                expected: width * height,
+                found: data.len(),
+            });
+        }
+
+        Ok(Self {
             width,
             height,
             data,
+        })
+    }
+
+    pub fn from_rows<const R: usize, const C: usize>(rows: [[Float; C]; R]) -> Self {
+        Self {
+            width: R,
+            height: C,
+            data: rows.as_flattened().to_vec(),
         }
     }
 
@@ -79,21 +108,16 @@ impl Matrix {
         }
     }
 
-    pub fn determinant(&self) -> f64 {
+    pub fn determinant(&self) -> Float {
         if self.width != self.height {
             panic!("cannot calculate determinant for non-square matrices");
         }
 
         if self.width == 2 {
-            self[(0, 0)] * self[(1, 1)] - self[(0, 1)] * self[(1, 0)]
-        } else {
-            let mut determinant = 0.0;
-            for x in 0..self.width {
-                determinant += self[(0, x)] * self.cofactor(0, x)
-            }
-
-            determinant
+            return determinant_2x2(&self.data);
         }
+
+        lu_determinant(&mut self.data.clone(), self.width)
     }
 
     pub fn submatrix(&self, row: usize, col: usize) -> Self {
@@ -114,12 +138,12 @@ impl Matrix {
         }
     }
 
-    pub fn minor(&self, row: usize, col: usize) -> f64 {
+    pub fn minor(&self, row: usize, col: usize) -> Float {
         self.submatrix(row, col).determinant()
     }
 
-    pub fn cofactor(&self, row: usize, col: usize) -> f64 {
-        if (row + col) % 2 != 0 {
+    pub fn cofactor(&self, row: usize, col: usize) -> Float {
+        if !(row + col).is_multiple_of(2) {
             return -self.minor(row, col);
         }
 
@@ -135,27 +159,192 @@ impl Matrix {
             panic!("cannot invert matrices with determinant of 0")
         }
 
-        let mut inverse = Matrix::size(self.width, self.height);
-        for row in 0..self.width {
-            for col in 0..self.width {
-                let cofactor = self.cofactor(row, col);
-                inverse[(col, row)] = (cofactor / self.determinant() * 100000.0).round() / 100000.0;
-            }
+        let mut inverse = Matrix::size(self.width, self.height).identity();
+        if !gauss_jordan_inverse(&mut self.data.clone(), &mut inverse.data, self.width) {
+            panic!("cannot invert matrices with determinant of 0")
         }
         inverse
     }
+
+    pub fn row(&self, row: usize) -> &[Float] {
+        self.check_row(row);
+
+        &self.data[row * self.height..(row + 1) * self.height]
+    }
+
+    pub fn col(&self, col: usize) -> Vec<Float> {
+        self.check_col(col);
+
+        self.data
+            .iter()
+            .skip(col)
+            .step_by(self.height)
+            .copied()
+            .collect()
+    }
+
+    pub fn set_row(&mut self, row: usize, values: &[Float]) {
+        self.check_row(row);
+        if values.len() != self.height {
+            panic!(
+                "cannot set a row of length {} from {} values",
+                self.height,
+                values.len()
+            );
+        }
+
+        self.data[row * self.height..(row + 1) * self.height].copy_from_slice(values);
+    }
+
+    pub fn set_col(&mut self, col: usize, values: &[Float]) {
+        self.check_col(col);
+        if values.len() != self.width {
+            panic!(
+                "cannot set a column of length {} from {} values",
+                self.width,
+                values.len()
+            );
+        }
+
+        for (row, x) in values.iter().enumerate() {
+            self[(row, col)] = *x;
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Float]> {
+        self.data.chunks(self.height.max(1)).take(self.width)
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = Vec<Float>> + '_ {
+        (0..self.height).map(|col| self.col(col))
+    }
+
+    fn check_row(&self, row: usize) {
+        if row >= self.width {
+            panic!(
+                "out of bounds! tried to get row {} for matrix size ({} {})",
+                row, self.width, self.height
+            );
+        }
+    }
+
+    fn check_col(&self, col: usize) {
+        if col >= self.height {
+            panic!(
+                "out of bounds! tried to get column {} for matrix size ({} {})",
+                col, self.width, self.height
+            );
+        }
+    }
+
+    pub fn try_add(self, other: Self) -> Result<Self> {
+        self.check_same_size(&other)?;
+
+        Ok(self + other)
+    }
+
+    pub fn try_sub(self, other: Self) -> Result<Self> {
+        self.check_same_size(&other)?;
+
+        Ok(self - other)
+    }
+
+    pub fn try_mul(self, other: Self) -> Result<Self> {
+        if self.height != other.width {
+            return Err(Error::DimensionMismatch {
+                left: (self.width, self.height),
+                right: (other.width, other.height),
+            });
+        }
+
+        Ok(self * other)
+    }
+
+    pub fn try_mul_tuple(self, other: Tuple) -> Result<Tuple> {
+        if self.height != 4 {
+            return Err(Error::DimensionMismatch {
+                left: (self.width, self.height),
+                right: (4, 1),
+            });
+        }
+
+        Ok(self * other)
+    }
+
+    pub fn try_determinant(&self) -> Result<Float> {
+        if self.width != self.height {
+            return Err(Error::NonSquareMatrix {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        Ok(self.determinant())
+    }
+
+    pub fn try_inverse(&self) -> Result<Self> {
+        if self.width != self.height {
+            return Err(Error::NonSquareMatrix {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        let mut inverse = Matrix::identity_matrix(self.width);
+        if !gauss_jordan_inverse(&mut self.data.clone(), &mut inverse.data, self.width) {
+            return Err(Error::SingularMatrix);
+        }
+
+        Ok(inverse)
+    }
+
+    pub fn checked_index(&self, row: usize, col: usize) -> Result<&Float> {
+        if row >= self.width || col >= self.height {
+            return Err(Error::OutOfBounds {
+                index: (row, col),
+                size: (self.width, self.height),
+            });
+        }
+
+        self.data
+            .get(col + row * self.height)
+            .ok_or(Error::OutOfBounds {
+                index: (row, col),
+                size: (self.width, self.height),
+            })
+    }
+
+    fn check_same_size(&self, other: &Self) -> Result<()> {
+        if self.width != other.width || self.height != other.height {
+            return Err(Error::DimensionMismatch {
+                left: (self.width, self.height),
+                right: (other.width, other.height),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn approx_eq(&self, other: &Self, epsilon: Float) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .all(|(x, y)| float_eq!(x, y, abs <= epsilon))
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_rows(f, self.data.chunks(self.height.max(1)))
+    }
 }
 
 impl PartialEq for Matrix {
     fn eq(&self, other: &Self) -> bool {
-        let result = (self.width == other.width) && (self.height == other.height);
-        if !result {
-            return result;
-        }
-        self.data
-            .iter()
-            .zip(other.data.iter())
-            .fold(true, |acc, (x, y)| acc && float_eq!(x, y, rmin <= 0.001))
+        self.approx_eq(other, approx::epsilon())
     }
 }
 
@@ -169,7 +358,7 @@ impl Add for Matrix {
             panic!("cannot add two matrices of different dimensions");
         }
 
-        let result: Vec<f64> = self
+        let result: Vec<Float> = self
             .data
             .iter()
             .zip(other.data.iter())
@@ -192,7 +381,7 @@ impl Sub for Matrix {
             panic!("cannot subtract two matrices of different dimensions");
         }
 
-        let result: Vec<f64> = self
+        let result: Vec<Float> = self
             .data
             .iter()
             .zip(other.data.iter())
@@ -207,10 +396,10 @@ impl Sub for Matrix {
     }
 }
 
-impl Mul<f64> for Matrix {
+impl Mul<Float> for Matrix {
     type Output = Matrix;
 
-    fn mul(self, other: f64) -> Self {
+    fn mul(self, other: Float) -> Self {
         Self {
             width: self.width,
             height: self.height,
@@ -219,14 +408,24 @@ impl Mul<f64> for Matrix {
     }
 }
 
-impl Mul<Matrix> for Matrix {
+impl Mul<&Matrix> for &Matrix {
     type Output = Matrix;
 
-    fn mul(self, other: Self) -> Self {
+    fn mul(self, other: &Matrix) -> Matrix {
         if self.height != other.width {
             panic!("number of columns in the first matrix should be equal to number of rows in the second matrix!");
         }
 
+        if self.width == 4 && self.height == 4 && other.height == 4 {
+            let result = simd::mul_mat4(&self.data, &other.data);
+
+            return Matrix {
+                width: 4,
+                height: 4,
+                data: result.to_vec(),
+            };
+        }
+
         let mut result = vec![];
 
         for i in 0..self.width {
@@ -235,11 +434,11 @@ impl Mul<Matrix> for Matrix {
                 for k in 0..self.height {
                     sum += self[(i, k)] * other[(k, j)]
                 }
-                result.push((sum * 100000.0).round() / 100000.0);
+                result.push(sum);
             }
         }
 
-        Self {
+        Matrix {
             width: self.width,
             height: other.height,
             data: result,
@@ -247,7 +446,27 @@ impl Mul<Matrix> for Matrix {
     }
 }
 
-impl Mul<Tuple> for Matrix {
+impl Mul<Matrix> for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: Self) -> Self {
+        &self * &other
+    }
+}
+
+impl MulAssign<&Matrix> for Matrix {
+    fn mul_assign(&mut self, other: &Matrix) {
+        *self = &*self * other;
+    }
+}
+
+impl MulAssign<Matrix> for Matrix {
+    fn mul_assign(&mut self, other: Matrix) {
+        *self = &*self * &other;
+    }
+}
+
+impl Mul<Tuple> for &Matrix {
     type Output = Tuple;
 
     fn mul(self, other: Tuple) -> Tuple {
@@ -255,9 +474,13 @@ impl Mul<Tuple> for Matrix {
             panic!("cannot multiply this matrix with a tuple!");
         }
 
+        if self.width == 4 {
+            return simd::mul_mat4_vec4(&self.data, other.into()).into();
+        }
+
         let tuple_matrix = Matrix::new(4, 1, vec![other.x, other.y, other.z, other.w]);
 
-        let result = self * tuple_matrix;
+        let result = self * &tuple_matrix;
 
         Tuple::new(
             result[(0, 0)],
@@ -268,10 +491,26 @@ impl Mul<Tuple> for Matrix {
     }
 }
 
+impl Mul<&Tuple> for &Matrix {
+    type Output = Tuple;
+
+    fn mul(self, other: &Tuple) -> Tuple {
+        self * *other
+    }
+}
+
+impl Mul<Tuple> for Matrix {
+    type Output = Tuple;
+
+    fn mul(self, other: Tuple) -> Tuple {
+        &self * other
+    }
+}
+
 impl Index<(usize, usize)> for Matrix {
-    type Output = f64;
+    type Output = Float;
 
-    fn index(&self, (row, col): (usize, usize)) -> &f64 {
+    fn index(&self, (row, col): (usize, usize)) -> &Float {
         match self.data.get(col + row * self.height) {
             Some(t) => t,
             None => panic!(
@@ -283,7 +522,7 @@ impl Index<(usize, usize)> for Matrix {
 }
 
 impl IndexMut<(usize, usize)> for Matrix {
-    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut f64 {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Float {
         match self.data.get_mut(col + row * self.height) {
             Some(t) => t,
             None => panic!(
@@ -294,9 +533,498 @@ impl IndexMut<(usize, usize)> for Matrix {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct SquareMatrix<const N: usize> {
+    pub data: [[Float; N]; N],
+}
+
+pub type Matrix2 = SquareMatrix<2>;
+pub type Matrix3 = SquareMatrix<3>;
+pub type Matrix4 = SquareMatrix<4>;
+
+impl<const N: usize> SquareMatrix<N> {
+    pub fn new(data: [[Float; N]; N]) -> Self {
+        Self { data }
+    }
+
+    pub fn identity() -> Self {
+        let mut data = [[0.0; N]; N];
+        for (i, row) in data.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+
+        Self { data }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut data = [[0.0; N]; N];
+        for (row, values) in self.data.iter().enumerate() {
+            for (col, x) in values.iter().enumerate() {
+                data[col][row] = *x;
+            }
+        }
+
+        Self { data }
+    }
+
+    pub fn row(&self, row: usize) -> [Float; N] {
+        match self.data.get(row) {
+            Some(r) => *r,
+            None => panic!(
+                "out of bounds! tried to get row {} for matrix size ({} {})",
+                row, N, N
+            ),
+        }
+    }
+
+    pub fn col(&self, col: usize) -> [Float; N] {
+        if col >= N {
+            panic!(
+                "out of bounds! tried to get column {} for matrix size ({} {})",
+                col, N, N
+            );
+        }
+
+        self.data.map(|row| row[col])
+    }
+
+    pub fn set_row(&mut self, row: usize, values: [Float; N]) {
+        match self.data.get_mut(row) {
+            Some(r) => *r = values,
+            None => panic!(
+                "out of bounds! tried to get row {} for matrix size ({} {})",
+                row, N, N
+            ),
+        }
+    }
+
+    pub fn set_col(&mut self, col: usize, values: [Float; N]) {
+        if col >= N {
+            panic!(
+                "out of bounds! tried to get column {} for matrix size ({} {})",
+                col, N, N
+            );
+        }
+
+        for (row, x) in self.data.iter_mut().zip(values) {
+            row[col] = x;
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Float; N]> {
+        self.data.iter()
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = [Float; N]> + '_ {
+        (0..N).map(|col| self.col(col))
+    }
+
+    // `M` has to be `N - 1`, which can't be written as a type yet on stable,
+    // so it's checked when the function is instantiated instead.
+    pub fn submatrix<const M: usize>(&self, row: usize, col: usize) -> SquareMatrix<M> {
+        const { assert!(M + 1 == N, "submatrix must be exactly one size smaller") };
+
+        let mut data = [[0.0; M]; M];
+        let rows = self.data.iter().enumerate().filter(|(r, _)| *r != row);
+        for (target, (_, values)) in data.iter_mut().zip(rows) {
+            let cols = values.iter().enumerate().filter(|(c, _)| *c != col);
+            for (x, (_, value)) in target.iter_mut().zip(cols) {
+                *x = *value;
+            }
+        }
+
+        SquareMatrix { data }
+    }
+
+    pub fn determinant(&self) -> Float {
+        if N == 2 {
+            return determinant_2x2(self.data.as_flattened());
+        }
+
+        let mut data = self.data;
+        lu_determinant(data.as_flattened_mut(), N)
+    }
+
+    pub fn is_invertible(&self) -> bool {
+        self.determinant() != 0.0
+    }
+
+    pub fn try_inverse(&self) -> Result<Self> {
+        let mut data = self.data;
+        let mut inverse = Self::identity();
+        if !gauss_jordan_inverse(data.as_flattened_mut(), inverse.data.as_flattened_mut(), N) {
+            return Err(Error::SingularMatrix);
+        }
+
+        Ok(inverse)
+    }
+
+    pub fn inverse(&self) -> Self {
+        match self.try_inverse() {
+            Ok(inverse) => inverse,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    pub fn approx_eq(&self, other: &Self, epsilon: Float) -> bool {
+        self.data
+            .as_flattened()
+            .iter()
+            .zip(other.data.as_flattened().iter())
+            .all(|(x, y)| float_eq!(x, y, abs <= epsilon))
+    }
+}
+
+macro_rules! impl_cofactor_expansion {
+    ($size:literal, $minor:literal) => {
+        impl SquareMatrix<$size> {
+            pub fn minor(&self, row: usize, col: usize) -> Float {
+                self.submatrix::<$minor>(row, col).determinant()
+            }
+
+            pub fn cofactor(&self, row: usize, col: usize) -> Float {
+                if !(row + col).is_multiple_of(2) {
+                    return -self.minor(row, col);
+                }
+
+                self.minor(row, col)
+            }
+        }
+    };
+}
+
+impl_cofactor_expansion!(3, 2);
+impl_cofactor_expansion!(4, 3);
+
+impl<const N: usize> fmt::Display for SquareMatrix<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_rows(f, self.data.iter().map(|row| row.as_slice()))
+    }
+}
+
+impl<const N: usize> PartialEq for SquareMatrix<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.approx_eq(other, approx::epsilon())
+    }
+}
+
+impl<const N: usize> Eq for SquareMatrix<N> {}
+
+impl<const N: usize> Add for SquareMatrix<N> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        let values = other.data.as_flattened().iter();
+        for (x, y) in self.data.as_flattened_mut().iter_mut().zip(values) {
+            *x += y;
+        }
+
+        self
+    }
+}
+
+impl<const N: usize> Sub for SquareMatrix<N> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        let values = other.data.as_flattened().iter();
+        for (x, y) in self.data.as_flattened_mut().iter_mut().zip(values) {
+            *x -= y;
+        }
+
+        self
+    }
+}
+
+impl<const N: usize> Mul<Float> for SquareMatrix<N> {
+    type Output = Self;
+
+    fn mul(mut self, other: Float) -> Self {
+        for x in self.data.as_flattened_mut() {
+            *x *= other;
+        }
+
+        self
+    }
+}
+
+impl<const N: usize> Mul<SquareMatrix<N>> for SquareMatrix<N> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut result = Self::new([[0.0; N]; N]);
+
+        if N == 4 {
+            let product = simd::mul_mat4(self.data.as_flattened(), other.data.as_flattened());
+            result.data.as_flattened_mut().copy_from_slice(&product);
+        } else {
+            for i in 0..N {
+                for j in 0..N {
+                    let mut sum = 0.0;
+                    for k in 0..N {
+                        sum += self.data[i][k] * other.data[k][j];
+                    }
+                    result.data[i][j] = sum;
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl<const N: usize> MulAssign<SquareMatrix<N>> for SquareMatrix<N> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl Mul<Tuple> for Matrix4 {
+    type Output = Tuple;
+
+    fn mul(self, other: Tuple) -> Tuple {
+        simd::mul_mat4_vec4(self.data.as_flattened(), other.into()).into()
+    }
+}
+
+// affine transforms keep w at 1, anything else (e.g. a perspective projection)
+// gets the usual homogeneous divide.
+impl Mul<Point> for Matrix4 {
+    type Output = Point;
+
+    fn mul(self, other: Point) -> Point {
+        let t = self * Tuple::from(other);
+        if t.w == 1.0 {
+            Point::new(t.x, t.y, t.z)
+        } else {
+            Point::new(t.x / t.w, t.y / t.w, t.z / t.w)
+        }
+    }
+}
+
+// a direction only has a meaning under affine transforms, which keep w at 0.
+impl Mul<Vector> for Matrix4 {
+    type Output = Vector;
+
+    fn mul(self, other: Vector) -> Vector {
+        let t = self * Tuple::from(other);
+        debug_assert!(
+            t.w.abs() <= approx::epsilon(),
+            "cannot transform a vector by a projective matrix!"
+        );
+        Vector::new(t.x, t.y, t.z)
+    }
+}
+
+impl<const N: usize> Index<(usize, usize)> for SquareMatrix<N> {
+    type Output = Float;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Float {
+        match self.data.get(row).and_then(|r| r.get(col)) {
+            Some(t) => t,
+            None => panic!(
+                "out of bounds! tried to get index of ({}, {}) for matrix size ({} {})",
+                row, col, N, N
+            ),
+        }
+    }
+}
+
+impl<const N: usize> IndexMut<(usize, usize)> for SquareMatrix<N> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Float {
+        match self.data.get_mut(row).and_then(|r| r.get_mut(col)) {
+            Some(t) => t,
+            None => panic!(
+                "out of bounds! tried to get index of ({}, {}) for matrix size ({} {})",
+                row, col, N, N
+            ),
+        }
+    }
+}
+
+impl<const N: usize> TryFrom<Matrix> for SquareMatrix<N> {
+    type Error = Error;
+
+    fn try_from(matrix: Matrix) -> Result<Self> {
+        if matrix.width != N || matrix.height != N || matrix.data.len() != N * N {
+            return Err(Error::DimensionMismatch {
+                left: (matrix.width, matrix.height),
+                right: (N, N),
+            });
+        }
+
+        let mut result = Self::new([[0.0; N]; N]);
+        result.data.as_flattened_mut().copy_from_slice(&matrix.data);
+
+        Ok(result)
+    }
+}
+
+impl<const R: usize, const C: usize> From<[[Float; C]; R]> for Matrix {
+    fn from(rows: [[Float; C]; R]) -> Self {
+        Matrix::from_rows(rows)
+    }
+}
+
+impl<const N: usize> From<[[Float; N]; N]> for SquareMatrix<N> {
+    fn from(rows: [[Float; N]; N]) -> Self {
+        Self::new(rows)
+    }
+}
+
+impl<const N: usize> From<SquareMatrix<N>> for Matrix {
+    fn from(matrix: SquareMatrix<N>) -> Self {
+        Matrix::new(N, N, matrix.data.as_flattened().to_vec())
+    }
+}
+
+// prints one `| a b c |` line per row, right-aligning every column to its widest value.
+fn fmt_rows<'a>(
+    f: &mut fmt::Formatter<'_>,
+    rows: impl Iterator<Item = &'a [Float]>,
+) -> fmt::Result {
+    let rows: Vec<Vec<String>> = rows
+        .map(|row| {
+            row.iter()
+                .map(|x| match f.precision() {
+                    Some(p) => format!("{:.*}", p, x),
+                    None => format!("{}", x),
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut widths = vec![];
+    for row in rows.iter() {
+        for (col, value) in row.iter().enumerate() {
+            if col == widths.len() {
+                widths.push(0);
+            }
+            widths[col] = widths[col].max(value.len());
+        }
+    }
+
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+
+        write!(f, "|")?;
+        for (value, width) in row.iter().zip(widths.iter()) {
+            write!(f, " {:>width$}", value, width = width)?;
+        }
+        write!(f, " |")?;
+    }
+
+    Ok(())
+}
+
+// both of these work on row-major `n * n` slices and use partial pivoting,
+// picking the largest remaining value in each column to keep the error down.
+fn pivot_row(a: &[Float], n: usize, col: usize) -> usize {
+    (col..n)
+        .max_by(|&i, &j| a[i * n + col].abs().total_cmp(&a[j * n + col].abs()))
+        .unwrap_or(col)
+}
+
+fn swap_rows(a: &mut [Float], n: usize, i: usize, j: usize) {
+    for k in 0..n {
+        a.swap(i * n + k, j * n + k);
+    }
+}
+
+// exact for small integer entries, and snapped to 0.0 below the same
+// tolerance the elimination uses.
+fn determinant_2x2(a: &[Float]) -> Float {
+    let determinant = a[0] * a[3] - a[1] * a[2];
+    let max = a.iter().fold(0.0 as Float, |max, x| max.max(x.abs()));
+    if determinant.abs() <= singular_tolerance(a, 2) * max {
+        0.0
+    } else {
+        determinant
+    }
+}
+
+// largest pivot magnitude that still counts as zero. elimination leaves
+// rounding noise of a few ulps of the biggest entry where a pivot of a
+// singular matrix should be, so an exact comparison with 0.0 isn't enough.
+fn singular_tolerance(a: &[Float], n: usize) -> Float {
+    let max = a.iter().fold(0.0 as Float, |max, x| max.max(x.abs()));
+    n as Float * Float::EPSILON * max
+}
+
+fn lu_determinant(a: &mut [Float], n: usize) -> Float {
+    let tolerance = singular_tolerance(a, n);
+    let mut determinant = 1.0;
+
+    for col in 0..n {
+        let pivot = pivot_row(a, n, col);
+        if a[pivot * n + col].abs() <= tolerance {
+            return 0.0;
+        }
+
+        if pivot != col {
+            swap_rows(a, n, pivot, col);
+            determinant = -determinant;
+        }
+
+        let p = a[col * n + col];
+        determinant *= p;
+
+        for row in col + 1..n {
+            let factor = a[row * n + col] / p;
+            for k in col..n {
+                a[row * n + k] -= factor * a[col * n + k];
+            }
+        }
+    }
+
+    determinant
+}
+
+// reduces `a` to the identity while applying the same row operations to
+// `inverse`, which has to start out as the identity. returns false if `a` is
+// singular.
+fn gauss_jordan_inverse(a: &mut [Float], inverse: &mut [Float], n: usize) -> bool {
+    let tolerance = singular_tolerance(a, n);
+
+    for col in 0..n {
+        let pivot = pivot_row(a, n, col);
+        if a[pivot * n + col].abs() <= tolerance {
+            return false;
+        }
+
+        if pivot != col {
+            swap_rows(a, n, pivot, col);
+            swap_rows(inverse, n, pivot, col);
+        }
+
+        let p = a[col * n + col];
+        for k in 0..n {
+            a[col * n + k] /= p;
+            inverse[col * n + k] /= p;
+        }
+
+        for row in (0..n).filter(|row| *row != col) {
+            let factor = a[row * n + col];
+            if factor == 0.0 {
+                continue;
+            }
+
+            for k in 0..n {
+                a[row * n + k] -= factor * a[col * n + k];
+                inverse[row * n + k] -= factor * inverse[col * n + k];
+            }
+        }
+    }
+
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use float_eq::assert_float_eq;
 
     #[test]
     fn test_new_matrix() {
@@ -362,30 +1090,26 @@ mod tests {
 
     #[test]
     fn test_mul_matrices_ok() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0,
-            ],
-        );
-
-        let other = Matrix::new(
-            4,
-            4,
-            vec![
-                -2.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, -1.0, 4.0, 3.0, 6.0, 5.0, 1.0, 2.0, 7.0, 8.0,
-            ],
-        );
-
-        let result = Matrix::new(
-            4,
-            4,
-            vec![
-                20.0, 22.0, 50.0, 48.0, 44.0, 54.0, 114.0, 108.0, 40.0, 58.0, 110.0, 102.0, 16.0,
-                26.0, 46.0, 42.0,
-            ],
-        );
+        let matrix = Matrix::from_rows([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 8.0, 7.0, 6.0],
+            [5.0, 4.0, 3.0, 2.0],
+        ]);
+
+        let other = Matrix::from_rows([
+            [-2.0, 1.0, 2.0, 3.0],
+            [3.0, 2.0, 1.0, -1.0],
+            [4.0, 3.0, 6.0, 5.0],
+            [1.0, 2.0, 7.0, 8.0],
+        ]);
+
+        let result = Matrix::from_rows([
+            [20.0, 22.0, 50.0, 48.0],
+            [44.0, 54.0, 114.0, 108.0],
+            [40.0, 58.0, 110.0, 102.0],
+            [16.0, 26.0, 46.0, 42.0],
+        ]);
 
         assert_eq!((matrix * other).data, result.data);
     }
@@ -403,13 +1127,12 @@ mod tests {
 
     #[test]
     fn test_mul_matrix_tuple_ok() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                1.0, 2.0, 3.0, 4.0, 2.0, 4.0, 4.0, 2.0, 8.0, 6.0, 4.0, 1.0, 0.0, 0.0, 0.0, 1.0,
-            ],
-        );
+        let matrix = Matrix::from_rows([
+            [1.0, 2.0, 3.0, 4.0],
+            [2.0, 4.0, 4.0, 2.0],
+            [8.0, 6.0, 4.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
         let tuple = Tuple::new(1.0, 2.0, 3.0, 1.0);
         let result = Tuple::new(18.0, 24.0, 33.0, 1.0);
 
@@ -418,27 +1141,24 @@ mod tests {
 
     #[test]
     fn test_cmp_matrix() {
-        let m1 = Matrix::new(
-            4,
-            4,
-            vec![
-                1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0,
-            ],
-        );
-        let m2 = Matrix::new(
-            4,
-            4,
-            vec![
-                1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0,
-            ],
-        );
-        let m3 = Matrix::new(
-            4,
-            4,
-            vec![
-                2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0,
-            ],
-        );
+        let m1 = Matrix::from_rows([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 8.0, 7.0, 6.0],
+            [5.0, 4.0, 3.0, 2.0],
+        ]);
+        let m2 = Matrix::from_rows([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 8.0, 7.0, 6.0],
+            [5.0, 4.0, 3.0, 2.0],
+        ]);
+        let m3 = Matrix::from_rows([
+            [2.0, 3.0, 4.0, 5.0],
+            [6.0, 7.0, 8.0, 9.0],
+            [8.0, 7.0, 6.0, 5.0],
+            [4.0, 3.0, 2.0, 1.0],
+        ]);
 
         assert_eq!(m1, m2);
         assert_ne!(m1, m3);
@@ -446,16 +1166,15 @@ mod tests {
 
     #[test]
     fn test_mul_identity_matrix() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                0.0, 1.0, 2.0, 4.0, 1.0, 2.0, 4.0, 8.0, 2.0, 4.0, 8.0, 17.0, 4.0, 8.0, 16.0, 32.0,
-            ],
-        );
+        let matrix = Matrix::from_rows([
+            [0.0, 1.0, 2.0, 4.0],
+            [1.0, 2.0, 4.0, 8.0],
+            [2.0, 4.0, 8.0, 17.0],
+            [4.0, 8.0, 16.0, 32.0],
+        ]);
         let identity = matrix.identity();
 
-        assert_eq!(matrix.clone() * identity, matrix);
+        assert_eq!(&matrix * &identity, matrix);
     }
 
     #[test]
@@ -468,20 +1187,18 @@ mod tests {
 
     #[test]
     fn test_transpose_matrix() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                0.0, 9.0, 3.0, 0.0, 9.0, 8.0, 0.0, 8.0, 1.0, 8.0, 5.0, 3.0, 0.0, 0.0, 5.0, 8.0,
-            ],
-        );
-        let transposed = Matrix::new(
-            4,
-            4,
-            vec![
-                0.0, 9.0, 1.0, 0.0, 9.0, 8.0, 8.0, 0.0, 3.0, 0.0, 5.0, 5.0, 0.0, 8.0, 3.0, 8.0,
-            ],
-        );
+        let matrix = Matrix::from_rows([
+            [0.0, 9.0, 3.0, 0.0],
+            [9.0, 8.0, 0.0, 8.0],
+            [1.0, 8.0, 5.0, 3.0],
+            [0.0, 0.0, 5.0, 8.0],
+        ]);
+        let transposed = Matrix::from_rows([
+            [0.0, 9.0, 1.0, 0.0],
+            [9.0, 8.0, 8.0, 0.0],
+            [3.0, 0.0, 5.0, 5.0],
+            [0.0, 8.0, 3.0, 8.0],
+        ]);
 
         assert_eq!(matrix.transpose(), transposed);
     }
@@ -503,30 +1220,52 @@ mod tests {
 
     #[test]
     fn test_det_matrix_3x3() {
-        let matrix = Matrix::new(3, 3, vec![1.0, 2.0, 6.0, -5.0, 8.0, -4.0, 2.0, 6.0, 4.0]);
-
-        assert_eq!(matrix.cofactor(0, 0), 56.0);
-        assert_eq!(matrix.cofactor(0, 1), 12.0);
-        assert_eq!(matrix.cofactor(0, 2), -46.0);
-        assert_eq!(matrix.determinant(), -196.0);
+        let matrix = Matrix::from_rows([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+
+        assert_float_eq!(matrix.cofactor(0, 0), 56.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(matrix.cofactor(0, 1), 12.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(
+            matrix.cofactor(0, 2),
+            -46.0,
+            rmax <= approx::DEFAULT_EPSILON
+        );
+        assert_float_eq!(
+            matrix.determinant(),
+            -196.0,
+            rmax <= approx::DEFAULT_EPSILON
+        );
     }
 
     #[test]
     fn test_det_matrix_4x4() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                -2.0, -8.0, 3.0, 5.0, -3.0, 1.0, 7.0, 3.0, 1.0, 2.0, -9.0, 6.0, -6.0, 7.0, 7.0,
-                -9.0,
-            ],
+        let matrix = Matrix::from_rows([
+            [-2.0, -8.0, 3.0, 5.0],
+            [-3.0, 1.0, 7.0, 3.0],
+            [1.0, 2.0, -9.0, 6.0],
+            [-6.0, 7.0, 7.0, -9.0],
+        ]);
+
+        assert_float_eq!(
+            matrix.cofactor(0, 0),
+            690.0,
+            rmax <= approx::DEFAULT_EPSILON
+        );
+        assert_float_eq!(
+            matrix.cofactor(0, 1),
+            447.0,
+            rmax <= approx::DEFAULT_EPSILON
+        );
+        assert_float_eq!(
+            matrix.cofactor(0, 2),
+            210.0,
+            rmax <= approx::DEFAULT_EPSILON
+        );
+        assert_float_eq!(matrix.cofactor(0, 3), 51.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(
+            matrix.determinant(),
+            -4071.0,
+            rmax <= approx::DEFAULT_EPSILON
         );
-
-        assert_eq!(matrix.cofactor(0, 0), 690.0);
-        assert_eq!(matrix.cofactor(0, 1), 447.0);
-        assert_eq!(matrix.cofactor(0, 2), 210.0);
-        assert_eq!(matrix.cofactor(0, 3), 51.0);
-        assert_eq!(matrix.determinant(), -4071.0);
     }
 
     #[test]
@@ -539,7 +1278,7 @@ mod tests {
 
     #[test]
     fn test_submatrix_3x3() {
-        let matrix = Matrix::new(3, 3, vec![1.0, 5.0, 0.0, -3.0, 2.0, -7.0, 0.0, 6.0, -3.0]);
+        let matrix = Matrix::from_rows([[1.0, 5.0, 0.0], [-3.0, 2.0, -7.0], [0.0, 6.0, -3.0]]);
         let submatrix = Matrix::new(2, 2, vec![-3.0, 2.0, 0.0, 6.0]);
 
         assert_eq!(matrix.submatrix(0, 2), submatrix);
@@ -547,21 +1286,20 @@ mod tests {
 
     #[test]
     fn test_submatrix_4x4() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                -6.0, 1.0, 1.0, 6.0, -8.0, 5.0, 8.0, 6.0, -1.0, 0.0, 8.0, 2.0, -7.0, 1.0, -1.0, 1.0,
-            ],
-        );
-        let submatrix = Matrix::new(3, 3, vec![-6.0, 1.0, 6.0, -8.0, 8.0, 6.0, -7.0, -1.0, 1.0]);
+        let matrix = Matrix::from_rows([
+            [-6.0, 1.0, 1.0, 6.0],
+            [-8.0, 5.0, 8.0, 6.0],
+            [-1.0, 0.0, 8.0, 2.0],
+            [-7.0, 1.0, -1.0, 1.0],
+        ]);
+        let submatrix = Matrix::from_rows([[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]]);
 
         assert_eq!(matrix.submatrix(2, 1), submatrix);
     }
 
     #[test]
     fn test_minor_3x3() {
-        let matrix = Matrix::new(3, 3, vec![3.0, 5.0, 0.0, 2.0, -1.0, -7.0, 6.0, -1.0, 5.0]);
+        let matrix = Matrix::from_rows([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
         let submatrix = matrix.submatrix(1, 0);
 
         assert_eq!(matrix.minor(1, 0), submatrix.determinant())
@@ -569,7 +1307,7 @@ mod tests {
 
     #[test]
     fn test_cofactor() {
-        let matrix = Matrix::new(3, 3, vec![3.0, 5.0, 0.0, 2.0, -1.0, -7.0, 6.0, -1.0, 5.0]);
+        let matrix = Matrix::from_rows([[3.0, 5.0, 0.0], [2.0, -1.0, -7.0], [6.0, -1.0, 5.0]]);
 
         assert_eq!(matrix.minor(0, 0), matrix.cofactor(0, 0));
         assert_eq!(matrix.minor(1, 0), -matrix.cofactor(1, 0));
@@ -577,130 +1315,513 @@ mod tests {
 
     #[test]
     fn test_is_invertible() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                6.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 6.0, 4.0, -9.0, 3.0, -7.0, 9.0, 1.0, 7.0, -6.0,
-            ],
+        let matrix = Matrix::from_rows([
+            [6.0, 4.0, 4.0, 4.0],
+            [5.0, 5.0, 7.0, 6.0],
+            [4.0, -9.0, 3.0, -7.0],
+            [9.0, 1.0, 7.0, -6.0],
+        ]);
+
+        assert_float_eq!(
+            matrix.determinant(),
+            -2120.0,
+            rmax <= approx::DEFAULT_EPSILON
         );
-
-        assert_eq!(matrix.determinant(), -2120.0);
         assert!(matrix.is_invertible());
     }
 
     #[test]
     fn test_invertible_fail() {
-        let matrix = Matrix::new(
-            4,
-            4,
-            vec![
-                -4.0, 2.0, -2.0, -3.0, 9.0, 6.0, 2.0, 6.0, 0.0, -5.0, 1.0, -5.0, 0.0, 0.0, 0.0, 0.0,
-            ],
-        );
-
-        assert_eq!(matrix.determinant(), 0.0);
+        let matrix = Matrix::from_rows([
+            [-4.0, 2.0, -2.0, -3.0],
+            [9.0, 6.0, 2.0, 6.0],
+            [0.0, -5.0, 1.0, -5.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ]);
+
+        assert_float_eq!(matrix.determinant(), 0.0, rmax <= approx::DEFAULT_EPSILON);
         assert!(!matrix.is_invertible());
     }
 
     #[test]
     fn test_invert_matrix() {
-        let m1 = Matrix::new(
-            4,
-            4,
-            vec![
-                -5.0, 2.0, 6.0, -8.0, 1.0, -5.0, 1.0, 8.0, 7.0, 7.0, -6.0, -7.0, 1.0, -3.0, 7.0,
-                4.0,
-            ],
-        );
+        let m1 = Matrix::from_rows([
+            [-5.0, 2.0, 6.0, -8.0],
+            [1.0, -5.0, 1.0, 8.0],
+            [7.0, 7.0, -6.0, -7.0],
+            [1.0, -3.0, 7.0, 4.0],
+        ]);
 
         let b1 = m1.inverse();
 
-        assert_eq!(m1.determinant(), 532.0);
-        assert_eq!(m1.cofactor(2, 3), -160.0);
-        assert_eq!(b1[(3, 2)], -0.30075);
-        assert_eq!(m1.cofactor(3, 2), 105.0);
-        assert_eq!(b1[(2, 3)], 0.19737);
+        assert_float_eq!(m1.determinant(), 532.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(m1.cofactor(2, 3), -160.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(b1[(3, 2)], -0.30075, abs <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(m1.cofactor(3, 2), 105.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(b1[(2, 3)], 0.19737, abs <= approx::DEFAULT_EPSILON);
         assert_eq!(
             b1,
-            Matrix::new(
-                4,
-                4,
-                vec![
-                    0.21805, 0.45113, 0.24060, -0.04511, -0.80827, -1.45677, -0.44361, 0.52068,
-                    -0.07895, -0.22368, -0.05263, 0.19737, -0.52256, -0.81391, -0.30075, 0.30639
-                ]
-            )
+            Matrix::from_rows([
+                [0.21805, 0.45113, 0.24060, -0.04511],
+                [-0.80827, -1.45677, -0.44361, 0.52068],
+                [-0.07895, -0.22368, -0.05263, 0.19737],
+                [-0.52256, -0.81391, -0.30075, 0.30639]
+            ])
         );
 
-        let m2 = Matrix::new(
-            4,
-            4,
-            vec![
-                8.0, -5.0, 9.0, 2.0, 7.0, 5.0, 6.0, 1.0, -6.0, 0.0, 9.0, 6.0, -3.0, 0.0, -9.0, -4.0,
-            ],
-        );
+        let m2 = Matrix::from_rows([
+            [8.0, -5.0, 9.0, 2.0],
+            [7.0, 5.0, 6.0, 1.0],
+            [-6.0, 0.0, 9.0, 6.0],
+            [-3.0, 0.0, -9.0, -4.0],
+        ]);
 
         let b2 = m2.inverse();
 
         assert_eq!(
             b2,
-            Matrix::new(
-                4,
-                4,
-                vec![
-                    -0.15385, -0.15385, -0.28205, -0.53846, -0.07692, 0.12308, 0.02564, 0.03077,
-                    0.35897, 0.35897, 0.43590, 0.92308, -0.69231, -0.69231, -0.76923, -1.92308
-                ]
-            )
+            Matrix::from_rows([
+                [-0.15385, -0.15385, -0.28205, -0.53846],
+                [-0.07692, 0.12308, 0.02564, 0.03077],
+                [0.35897, 0.35897, 0.43590, 0.92308],
+                [-0.69231, -0.69231, -0.76923, -1.92308]
+            ])
         );
 
-        let m3 = Matrix::new(
-            4,
-            4,
-            vec![
-                9.0, 3.0, 0.0, 9.0, -5.0, -2.0, -6.0, -3.0, -4.0, 9.0, 6.0, 4.0, -7.0, 6.0, 6.0,
-                2.0,
-            ],
-        );
+        let m3 = Matrix::from_rows([
+            [9.0, 3.0, 0.0, 9.0],
+            [-5.0, -2.0, -6.0, -3.0],
+            [-4.0, 9.0, 6.0, 4.0],
+            [-7.0, 6.0, 6.0, 2.0],
+        ]);
 
         let b3 = m3.inverse();
 
         assert_eq!(
             b3,
-            Matrix::new(
-                4,
-                4,
-                vec![
-                    -0.04074, -0.07778, 0.14444, -0.22222, -0.07778, 0.03333, 0.36667, -0.33333,
-                    -0.02901, -0.14630, -0.10926, 0.12963, 0.17778, 0.06667, -0.26667, 0.33333
-                ]
-            )
+            Matrix::from_rows([
+                [-0.04074, -0.07778, 0.14444, -0.22222],
+                [-0.07778, 0.03333, 0.36667, -0.33333],
+                [-0.02901, -0.14630, -0.10926, 0.12963],
+                [0.17778, 0.06667, -0.26667, 0.33333]
+            ])
         )
     }
 
     #[test]
     fn test_mul_product_with_inverse() {
-        let a = Matrix::new(
-            4,
-            4,
-            vec![
-                3.0, -9.0, 7.0, 3.0, 3.0, -8.0, 2.0, -9.0, -4.0, 4.0, 4.0, 1.0, -6.0, 5.0, -1.0,
-                1.0,
-            ],
+        let a = Matrix::from_rows([
+            [3.0, -9.0, 7.0, 3.0],
+            [3.0, -8.0, 2.0, -9.0],
+            [-4.0, 4.0, 4.0, 1.0],
+            [-6.0, 5.0, -1.0, 1.0],
+        ]);
+
+        let b = Matrix::from_rows([
+            [8.0, 2.0, 2.0, 2.0],
+            [3.0, -1.0, 7.0, 0.0],
+            [7.0, 0.0, 5.0, 4.0],
+            [6.0, -2.0, 0.0, 5.0],
+        ]);
+
+        let c = &a * &b;
+        let result = c * b.inverse();
+
+        assert_eq!(a, result);
+    }
+
+    #[test]
+    fn test_fixed_matrix_identity() {
+        let matrix = Matrix4::new([
+            [0.0, 1.0, 2.0, 4.0],
+            [1.0, 2.0, 4.0, 8.0],
+            [2.0, 4.0, 8.0, 17.0],
+            [4.0, 8.0, 16.0, 32.0],
+        ]);
+        let tuple = Tuple::new(1.0, 2.0, 3.0, 4.0);
+
+        assert_eq!(matrix * Matrix4::identity(), matrix);
+        assert_eq!(Matrix4::identity() * tuple, tuple);
+        assert_eq!(Matrix3::identity().transpose(), Matrix3::identity());
+    }
+
+    #[test]
+    fn test_fixed_matrix_mul() {
+        let matrix = Matrix4::new([
+            [1.0, 2.0, 3.0, 4.0],
+            [2.0, 4.0, 4.0, 2.0],
+            [8.0, 6.0, 4.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let tuple = Tuple::new(1.0, 2.0, 3.0, 1.0);
+
+        assert_eq!(matrix * tuple, Tuple::new(18.0, 24.0, 33.0, 1.0));
+    }
+
+    #[test]
+    fn test_fixed_matrix_mul_2x2() {
+        let a = Matrix2::new([[1.0, 2.0], [3.0, 4.0]]);
+        let b = Matrix2::new([[0.0, 1.0], [1.0, 0.0]]);
+
+        assert_eq!(a * b, Matrix2::new([[2.0, 1.0], [4.0, 3.0]]));
+        assert_eq!(a + b - b, a);
+    }
+
+    #[test]
+    fn test_fixed_matrix_det() {
+        let m2 = Matrix2::new([[1.0, 5.0], [-3.0, 2.0]]);
+        let m3 = Matrix3::new([[1.0, 2.0, 6.0], [-5.0, 8.0, -4.0], [2.0, 6.0, 4.0]]);
+        let m4 = Matrix4::new([
+            [-2.0, -8.0, 3.0, 5.0],
+            [-3.0, 1.0, 7.0, 3.0],
+            [1.0, 2.0, -9.0, 6.0],
+            [-6.0, 7.0, 7.0, -9.0],
+        ]);
+
+        assert_float_eq!(m2.determinant(), 17.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(m3.cofactor(0, 2), -46.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(m3.determinant(), -196.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(m4.cofactor(0, 3), 51.0, rmax <= approx::DEFAULT_EPSILON);
+        assert_float_eq!(m4.determinant(), -4071.0, rmax <= approx::DEFAULT_EPSILON);
+    }
+
+    #[test]
+    fn test_fixed_submatrix() {
+        let matrix = Matrix4::new([
+            [-6.0, 1.0, 1.0, 6.0],
+            [-8.0, 5.0, 8.0, 6.0],
+            [-1.0, 0.0, 8.0, 2.0],
+            [-7.0, 1.0, -1.0, 1.0],
+        ]);
+        let submatrix = Matrix3::new([[-6.0, 1.0, 6.0], [-8.0, 8.0, 6.0], [-7.0, -1.0, 1.0]]);
+
+        assert_eq!(matrix.submatrix(2, 1), submatrix);
+        assert_eq!(
+            submatrix.submatrix(0, 2),
+            Matrix2::new([[-8.0, 8.0], [-7.0, -1.0]])
+        );
+    }
+
+    #[test]
+    fn test_fixed_matrix_inverse() {
+        let a = Matrix4::new([
+            [3.0, -9.0, 7.0, 3.0],
+            [3.0, -8.0, 2.0, -9.0],
+            [-4.0, 4.0, 4.0, 1.0],
+            [-6.0, 5.0, -1.0, 1.0],
+        ]);
+        let b = Matrix4::new([
+            [8.0, 2.0, 2.0, 2.0],
+            [3.0, -1.0, 7.0, 0.0],
+            [7.0, 0.0, 5.0, 4.0],
+            [6.0, -2.0, 0.0, 5.0],
+        ]);
+
+        assert_eq!(a * b * b.inverse(), a);
+    }
+
+    #[test]
+    fn test_fixed_matrix_conversion() {
+        let matrix = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        let fixed = Matrix2::try_from(matrix.clone()).unwrap();
+
+        assert_eq!(fixed[(1, 0)], 3.0);
+        assert_eq!(Matrix::from(fixed), matrix);
+        assert!(Matrix4::try_from(matrix).is_err());
+    }
+
+    #[test]
+    fn test_mul_no_rounding() {
+        let a = Matrix4::new([
+            [0.000001, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let tuple = Tuple::new(3.0, 0.0, 0.0, 0.0);
+
+        assert_eq!((a * a)[(0, 0)], 0.000001 * 0.000001);
+        assert_eq!((a * tuple).x, 0.000003);
+        assert_eq!(a.inverse()[(0, 0)], 1.0 / 0.000001);
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let a = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        let b = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.001]);
+
+        assert!(!a.approx_eq(&b, approx::DEFAULT_EPSILON));
+        assert!(a.approx_eq(&b, 0.01));
+        assert!(!a.approx_eq(&Matrix::size(1, 4), 0.01));
+    }
+
+    #[test]
+    fn test_try_ops_matrix() {
+        let matrix = Matrix::new(2, 2, vec![1.0; 4]);
+        let other = Matrix::size(3, 2);
+
+        assert_eq!(
+            matrix.clone().try_add(matrix.clone()).unwrap(),
+            Matrix::new(2, 2, vec![2.0; 4])
         );
+        assert!(matches!(
+            matrix.clone().try_sub(other.clone()),
+            Err(Error::DimensionMismatch {
+                left: (2, 2),
+                right: (3, 2)
+            })
+        ));
+        assert!(matrix.clone().try_mul(other).is_err());
+        assert!(matrix.try_mul_tuple(Tuple::point(1.0, 2.0, 3.0)).is_err());
+    }
+
+    #[test]
+    fn test_mul_point_projective() {
+        // maps z onto w, like the divide in a perspective projection.
+        let mut m = Matrix4::identity();
+        m[(3, 2)] = 1.0;
+        m[(3, 3)] = 0.0;
 
-        let b = Matrix::new(
-            4,
-            4,
-            vec![
-                8.0, 2.0, 2.0, 2.0, 3.0, -1.0, 7.0, 0.0, 7.0, 0.0, 5.0, 4.0, 6.0, -2.0, 0.0, 5.0,
-            ],
+        assert_eq!(m * Point::new(2.0, 4.0, 2.0), Point::new(1.0, 2.0, 1.0));
+        assert_eq!(
+            Matrix4::identity() * Point::new(2.0, 4.0, 2.0),
+            Point::new(2.0, 4.0, 2.0)
         );
+    }
 
-        let c = a.clone() * b.clone();
-        let result = c * b.inverse();
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cannot transform a vector by a projective matrix!")]
+    fn test_mul_vector_projective() {
+        let mut m = Matrix4::identity();
+        m[(3, 2)] = 1.0;
 
-        assert_eq!(a, result);
+        let _ = m * Vector::new(0.0, 0.0, 1.0);
+    }
+
+    #[test]
+    fn test_nearly_singular_matrix() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        let scaled: Vec<_> = data.iter().map(|x| x / 10.0).collect();
+
+        for data in [data, scaled] {
+            let m = Matrix::new(3, 3, data.clone());
+            assert_eq!(m.determinant(), 0.0);
+            assert!(!m.is_invertible());
+            assert!(matches!(m.try_inverse(), Err(Error::SingularMatrix)));
+
+            let m = Matrix3::new([
+                [data[0], data[1], data[2]],
+                [data[3], data[4], data[5]],
+                [data[6], data[7], data[8]],
+            ]);
+            assert!(!m.is_invertible());
+            assert!(matches!(m.try_inverse(), Err(Error::SingularMatrix)));
+        }
+
+        let m = Matrix::new(2, 2, vec![0.1, 0.2, 0.3, 0.6]);
+        assert!(!m.is_invertible());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot invert matrices with determinant of 0")]
+    fn test_inverse_nearly_singular() {
+        Matrix::new(3, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]).inverse();
+    }
+
+    #[test]
+    fn test_try_inverse() {
+        let singular = Matrix::new(2, 2, vec![1.0, 2.0, 2.0, 4.0]);
+
+        assert!(matches!(singular.try_inverse(), Err(Error::SingularMatrix)));
+        let m = Matrix::new(2, 2, vec![4.0, 7.0, 2.0, 6.0]);
+        assert_eq!(m.try_inverse().unwrap(), m.inverse());
+        assert!(matches!(
+            Matrix::size(3, 4).try_inverse(),
+            Err(Error::NonSquareMatrix {
+                width: 3,
+                height: 4
+            })
+        ));
+        assert!(matches!(
+            Matrix4::new([[0.0; 4]; 4]).try_inverse(),
+            Err(Error::SingularMatrix)
+        ));
+        assert_eq!(
+            Matrix4::identity().try_inverse().unwrap(),
+            Matrix4::identity()
+        );
+    }
+
+    #[test]
+    fn test_checked_index_matrix() {
+        let matrix = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert_eq!(matrix.checked_index(1, 0).unwrap(), &3.0);
+        assert!(matches!(
+            matrix.checked_index(0, 2),
+            Err(Error::OutOfBounds {
+                index: (0, 2),
+                size: (2, 2)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_inverse_needs_pivoting() {
+        let matrix = Matrix3::new([[0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]]);
+
+        assert_eq!(matrix.determinant(), 1.0);
+        assert_eq!(matrix.inverse(), matrix.transpose());
+    }
+
+    #[test]
+    fn test_invert_matrix_6x6() {
+        let mut data = vec![];
+        for row in 0..6 {
+            for col in 0..6 {
+                data.push(if row == col {
+                    10.0
+                } else {
+                    (row * 6 + col) as Float % 7.0
+                });
+            }
+        }
+        let matrix = Matrix::new(6, 6, data);
+        let identity = Matrix::identity_matrix(6);
+
+        assert_eq!(&matrix * &matrix.inverse(), identity);
+        let expansion: Float = (0..6)
+            .map(|col| matrix[(0, col)] * matrix.cofactor(0, col))
+            .sum();
+        assert_float_eq!(
+            matrix.determinant(),
+            expansion,
+            rmax <= approx::DEFAULT_EPSILON
+        );
+    }
+
+    #[test]
+    fn test_mul_assign_matrix() {
+        let a = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        let b = Matrix::new(2, 2, vec![0.0, 1.0, 1.0, 0.0]);
+        let expected = &a * &b;
+
+        let mut c = a.clone();
+        c *= &b;
+        assert_eq!(c, expected);
+
+        let mut d = Matrix4::identity();
+        d *= Matrix4::identity() * 2.0;
+        assert_eq!(d, Matrix4::identity() * 2.0);
+    }
+
+    #[test]
+    fn test_mul_matrix_tuple_ref() {
+        let matrix = Matrix::identity_matrix(4) * 2.0;
+        let tuple = Tuple::new(1.0, 2.0, 3.0, 4.0);
+
+        assert_eq!(Mul::mul(&matrix, &tuple), tuple * 2.0);
+        assert_eq!(&matrix * tuple, tuple * 2.0);
+        assert_eq!(matrix.data[0], 2.0);
+    }
+
+    #[test]
+    fn test_display_matrix() {
+        let matrix = Matrix::new(2, 3, vec![1.0, -20.0, 3.5, 100.0, 0.0, -1.0]);
+
+        assert_eq!(matrix.to_string(), "|   1 -20 3.5 |\n| 100   0  -1 |");
+        assert_eq!(
+            format!("{:.1}", Matrix2::identity()),
+            "| 1.0 0.0 |\n| 0.0 1.0 |"
+        );
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let matrix = Matrix::from_rows([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+
+        assert_eq!(
+            matrix,
+            Matrix::new(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
+        );
+        assert_eq!(matrix[(1, 0)], 4.0);
+        assert_eq!(
+            Matrix::from([[1.0, 0.0], [0.0, 1.0]]),
+            Matrix::identity_matrix(2)
+        );
+        assert_eq!(
+            Matrix4::from([
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ]),
+            Matrix4::identity()
+        );
+    }
+
+    #[test]
+    fn test_try_new_matrix() {
+        assert!(Matrix::try_new(2, 2, vec![1.0; 4]).is_ok());
+        assert!(matches!(
+            Matrix::try_new(2, 2, vec![1.0]),
+            Err(Error::InvalidDataLength {
+                expected: 4,
+                found: 1
+            })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected 4 values for matrix data, got 1")]
+    fn test_new_matrix_invalid_length() {
+        Matrix::new(2, 2, vec![1.0]);
+    }
+
+    #[test]
+    fn test_rows_and_cols_matrix() {
+        let mut matrix = Matrix::from_rows([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+
+        assert_eq!(matrix.row(1), &[4.0, 5.0, 6.0]);
+        assert_eq!(matrix.col(2), vec![3.0, 6.0]);
+        assert_eq!(matrix.rows().count(), 2);
+        assert_eq!(matrix.cols().collect::<Vec<_>>()[0], vec![1.0, 4.0]);
+
+        matrix.set_row(0, &[7.0, 8.0, 9.0]);
+        matrix.set_col(1, &[0.0, -1.0]);
+
+        assert_eq!(
+            matrix,
+            Matrix::from_rows([[7.0, 0.0, 9.0], [4.0, -1.0, 6.0]])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot set a column of length 2 from 3 values")]
+    fn test_set_col_matrix_invalid_length() {
+        let mut matrix = Matrix::size(2, 3);
+        matrix.set_col(0, &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds! tried to get row 2 for matrix size (2 3)")]
+    fn test_row_matrix_out_of_bounds() {
+        Matrix::size(2, 3).row(2);
+    }
+
+    #[test]
+    fn test_rows_and_cols_fixed_matrix() {
+        let mut matrix = Matrix3::identity();
+        matrix.set_row(0, [1.0, 2.0, 3.0]);
+        matrix.set_col(2, [4.0, 5.0, 6.0]);
+
+        assert_eq!(matrix.row(0), [1.0, 2.0, 4.0]);
+        assert_eq!(matrix.col(2), [4.0, 5.0, 6.0]);
+        assert_eq!(matrix.col(0), [1.0, 0.0, 0.0]);
+        assert_eq!(
+            matrix.rows().copied().collect::<Vec<_>>(),
+            matrix.transpose().cols().collect::<Vec<_>>()
+        );
     }
 }
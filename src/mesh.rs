@@ -0,0 +1,689 @@
+use crate::{
+    consts::{PI, TAU},
+    matrix::Matrix4,
+    tuple::Tuple,
+    Float,
+};
+use std::collections::HashMap;
+
+// indexed triangle mesh. vertex attributes live in shared buffers and each
+// triangle is three indices into them, so a vertex used by several triangles
+// is only stored once. `normals` and `uvs` are either empty or the same length
+// as `positions`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub uvs: Vec<(Float, Float)>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<Tuple>, triangles: Vec<[usize; 3]>) -> Self {
+        if let Some(i) = triangles.iter().flatten().find(|i| **i >= positions.len()) {
+            panic!(
+                "triangle index {} out of bounds for mesh with {} vertices!",
+                i,
+                positions.len()
+            );
+        }
+
+        Self {
+            positions,
+            normals: vec![],
+            uvs: vec![],
+            triangles,
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn triangle(&self, i: usize) -> [Tuple; 3] {
+        self.triangles[i].map(|v| self.positions[v])
+    }
+
+    // unnormalized, so its magnitude is twice the triangle's area. zero for
+    // degenerate triangles.
+    pub fn face_normal(&self, i: usize) -> Tuple {
+        let [a, b, c] = self.triangle(i);
+        (b - a).cross(&(c - a))
+    }
+
+    // replaces `normals` with area-weighted averages of the adjacent face
+    // normals. faces meeting at more than `crease_angle` (in radians) don't
+    // smooth into each other; vertices on such a crease get split so each side
+    // keeps its own normal. degenerate faces end up with zero normals.
+    pub fn compute_smooth_normals(&mut self, crease_angle: Float) {
+        let face_normals: Vec<Tuple> = (0..self.triangles.len())
+            .map(|i| self.face_normal(i))
+            .collect();
+        let min_cos = crease_angle.cos();
+        let smooths_into = |a: usize, b: usize| {
+            let (na, nb) = (face_normals[a], face_normals[b]);
+            let len = na.magnitude() * nb.magnitude();
+            a == b || (len > 0.0 && (na * nb) / len >= min_cos)
+        };
+
+        let mut adjacent = vec![vec![]; self.positions.len()];
+        for (face, triangle) in self.triangles.iter().enumerate() {
+            for v in triangle {
+                adjacent[*v].push(face);
+            }
+        }
+
+        let mut normals = vec![Tuple::vector(0.0, 0.0, 0.0); self.positions.len()];
+        let mut assigned = vec![false; self.positions.len()];
+        for (v, faces) in adjacent.iter().enumerate() {
+            // normals already used for this vertex, and the index holding each.
+            let mut seen: Vec<(Tuple, usize)> = vec![];
+
+            for &face in faces {
+                let mut sum = Tuple::vector(0.0, 0.0, 0.0);
+                for &other in faces.iter().filter(|other| smooths_into(face, **other)) {
+                    sum += face_normals[other];
+                }
+                let normal = if sum.magnitude() > 0.0 {
+                    sum.normalize()
+                } else {
+                    sum
+                };
+
+                let index = match seen.iter().find(|(n, _)| *n == normal) {
+                    Some((_, index)) => *index,
+                    None if !assigned[v] => {
+                        assigned[v] = true;
+                        normals[v] = normal;
+                        v
+                    }
+                    None => {
+                        self.positions.push(self.positions[v]);
+                        if !self.uvs.is_empty() {
+                            self.uvs.push(self.uvs[v]);
+                        }
+                        normals.push(normal);
+                        self.positions.len() - 1
+                    }
+                };
+                seen.push((normal, index));
+
+                for corner in self.triangles[face].iter_mut().filter(|c| **c == v) {
+                    *corner = index;
+                }
+            }
+        }
+
+        self.normals = normals;
+    }
+
+    // positions go through `transform` directly, normals through its inverse
+    // transpose so they stay perpendicular to the surface.
+    pub fn transform(&self, transform: &Matrix4) -> Self {
+        let normal_transform = transform.inverse().transpose();
+
+        Self {
+            positions: self.positions.iter().map(|p| *transform * *p).collect(),
+            normals: self
+                .normals
+                .iter()
+                .map(|n| {
+                    let mut n = normal_transform * *n;
+                    n.w = 0.0;
+                    n.normalize()
+                })
+                .collect(),
+            uvs: self.uvs.clone(),
+            triangles: self.triangles.clone(),
+        }
+    }
+}
+
+// generators for common shapes. everything is centered on the origin and fits
+// the same extents as the book's primitives: the sphere and cube span -1 to 1,
+// the plane lies in xz at y = 0. triangles wind counter-clockwise seen from
+// outside, and every generator fills in normals and, except the icosphere, uvs.
+impl Mesh {
+    // a square from -1 to 1 in x and z, facing +y.
+    pub fn plane(x_segments: usize, z_segments: usize) -> Self {
+        assert!(
+            x_segments >= 1 && z_segments >= 1,
+            "plane needs at least 1 segment along each axis!"
+        );
+
+        let mut mesh = Mesh::new(vec![], vec![]);
+        mesh.push_grid(
+            Tuple::point(-1.0, 0.0, 1.0),
+            Tuple::vector(2.0, 0.0, 0.0),
+            Tuple::vector(0.0, 0.0, -2.0),
+            x_segments,
+            z_segments,
+        );
+        mesh
+    }
+
+    // each face is a `segments` by `segments` grid with its own vertices, so
+    // the edges stay sharp.
+    pub fn cube(segments: usize) -> Self {
+        assert!(segments >= 1, "cube needs at least 1 segment per edge!");
+
+        let mut mesh = Mesh::new(vec![], vec![]);
+        let faces = [
+            ((1.0, -1.0, 1.0), (0.0, 0.0, -2.0), (0.0, 2.0, 0.0)),
+            ((-1.0, -1.0, -1.0), (0.0, 0.0, 2.0), (0.0, 2.0, 0.0)),
+            ((-1.0, 1.0, 1.0), (2.0, 0.0, 0.0), (0.0, 0.0, -2.0)),
+            ((-1.0, -1.0, -1.0), (2.0, 0.0, 0.0), (0.0, 0.0, 2.0)),
+            ((-1.0, -1.0, 1.0), (2.0, 0.0, 0.0), (0.0, 2.0, 0.0)),
+            ((1.0, -1.0, -1.0), (-2.0, 0.0, 0.0), (0.0, 2.0, 0.0)),
+        ];
+
+        for ((ox, oy, oz), (ux, uy, uz), (vx, vy, vz)) in faces {
+            mesh.push_grid(
+                Tuple::point(ox, oy, oz),
+                Tuple::vector(ux, uy, uz),
+                Tuple::vector(vx, vy, vz),
+                segments,
+                segments,
+            );
+        }
+
+        mesh
+    }
+
+    // unit sphere with `segments` slices around y and `rings` stacks from
+    // pole to pole. the seam and poles get duplicated vertices for the uvs.
+    pub fn uv_sphere(segments: usize, rings: usize) -> Self {
+        assert!(segments >= 3, "uv sphere needs at least 3 segments!");
+        assert!(rings >= 2, "uv sphere needs at least 2 rings!");
+
+        let mut mesh = Mesh::new(vec![], vec![]);
+
+        for ring in 0..=rings {
+            let theta = PI * ring as Float / rings as Float;
+            for segment in 0..=segments {
+                let phi = TAU * segment as Float / segments as Float;
+                let n = Tuple::vector(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+
+                mesh.positions.push(Tuple::point(n.x, n.y, n.z));
+                mesh.normals.push(n);
+                mesh.uvs.push((
+                    segment as Float / segments as Float,
+                    1.0 - ring as Float / rings as Float,
+                ));
+            }
+        }
+
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let [a, b, c, d] = quad_indices(ring, segment, segments + 1);
+                if ring > 0 {
+                    mesh.triangles.push([a, d, c]);
+                }
+                if ring + 1 < rings {
+                    mesh.triangles.push([a, c, b]);
+                }
+            }
+        }
+
+        mesh
+    }
+
+    // unit sphere made by repeatedly splitting an icosahedron, which spreads
+    // triangles far more evenly than `uv_sphere`. there's no seam to hang uvs
+    // on, so `uvs` is left empty.
+    pub fn icosphere(subdivisions: usize) -> Self {
+        let t = (1.0 + (5.0 as Float).sqrt()) / 2.0;
+        let vertex = |x: Float, y: Float, z: Float| {
+            let n = Tuple::vector(x, y, z).normalize();
+            Tuple::point(n.x, n.y, n.z)
+        };
+
+        let mut positions = vec![
+            vertex(-1.0, t, 0.0),
+            vertex(1.0, t, 0.0),
+            vertex(-1.0, -t, 0.0),
+            vertex(1.0, -t, 0.0),
+            vertex(0.0, -1.0, t),
+            vertex(0.0, 1.0, t),
+            vertex(0.0, -1.0, -t),
+            vertex(0.0, 1.0, -t),
+            vertex(t, 0.0, -1.0),
+            vertex(t, 0.0, 1.0),
+            vertex(-t, 0.0, -1.0),
+            vertex(-t, 0.0, 1.0),
+        ];
+        let mut triangles = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+            let mut midpoint = |a: usize, b: usize| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let m = Tuple::lerp(positions[a], positions[b], 0.5);
+                    positions.push(vertex(m.x, m.y, m.z));
+                    positions.len() - 1
+                })
+            };
+
+            triangles = triangles
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let mut mesh = Mesh::new(positions, triangles);
+        mesh.normals = mesh
+            .positions
+            .iter()
+            .map(|p| Tuple::vector(p.x, p.y, p.z))
+            .collect();
+        mesh
+    }
+
+    // torus around the y axis. `segments` divide the ring, `sides` divide the
+    // tube.
+    pub fn torus(major_radius: Float, minor_radius: Float, segments: usize, sides: usize) -> Self {
+        assert!(segments >= 3, "torus needs at least 3 segments!");
+        assert!(sides >= 3, "torus needs at least 3 sides!");
+
+        let mut mesh = Mesh::new(vec![], vec![]);
+
+        for segment in 0..=segments {
+            let phi = TAU * segment as Float / segments as Float;
+            for side in 0..=sides {
+                let theta = TAU * side as Float / sides as Float;
+                let n = Tuple::vector(
+                    theta.cos() * phi.cos(),
+                    theta.sin(),
+                    theta.cos() * phi.sin(),
+                );
+                let ring = major_radius + minor_radius * theta.cos();
+
+                mesh.positions.push(Tuple::point(
+                    ring * phi.cos(),
+                    minor_radius * theta.sin(),
+                    ring * phi.sin(),
+                ));
+                mesh.normals.push(n);
+                mesh.uvs.push((
+                    segment as Float / segments as Float,
+                    side as Float / sides as Float,
+                ));
+            }
+        }
+
+        for segment in 0..segments {
+            for side in 0..sides {
+                let [a, b, c, d] = quad_indices(segment, side, sides + 1);
+                mesh.triangles.push([a, d, c]);
+                mesh.triangles.push([a, c, b]);
+            }
+        }
+
+        mesh
+    }
+
+    // unit radius around the y axis, from y = -1 to 1. `capped` adds flat end
+    // caps with their own vertices.
+    pub fn cylinder(segments: usize, height_segments: usize, capped: bool) -> Self {
+        assert!(segments >= 3, "cylinder needs at least 3 segments!");
+        assert!(
+            height_segments >= 1,
+            "cylinder needs at least 1 height segment!"
+        );
+
+        let mut mesh = Mesh::new(vec![], vec![]);
+
+        for segment in 0..=segments {
+            let phi = TAU * segment as Float / segments as Float;
+            for step in 0..=height_segments {
+                let v = step as Float / height_segments as Float;
+
+                mesh.positions
+                    .push(Tuple::point(phi.cos(), 2.0 * v - 1.0, phi.sin()));
+                mesh.normals.push(Tuple::vector(phi.cos(), 0.0, phi.sin()));
+                mesh.uvs.push((segment as Float / segments as Float, v));
+            }
+        }
+
+        for segment in 0..segments {
+            for step in 0..height_segments {
+                let [a, b, c, d] = quad_indices(segment, step, height_segments + 1);
+                mesh.triangles.push([a, d, c]);
+                mesh.triangles.push([a, c, b]);
+            }
+        }
+
+        if capped {
+            for y in [1.0, -1.0] {
+                let center = mesh.positions.len();
+                let normal = Tuple::vector(0.0, y, 0.0);
+                mesh.positions.push(Tuple::point(0.0, y, 0.0));
+                mesh.normals.push(normal);
+                mesh.uvs.push((0.5, 0.5));
+
+                for segment in 0..segments {
+                    let phi = TAU * segment as Float / segments as Float;
+                    mesh.positions.push(Tuple::point(phi.cos(), y, phi.sin()));
+                    mesh.normals.push(normal);
+                    mesh.uvs
+                        .push((0.5 + phi.cos() / 2.0, 0.5 + phi.sin() / 2.0));
+                }
+
+                for segment in 0..segments {
+                    let p = center + 1 + segment;
+                    let q = center + 1 + (segment + 1) % segments;
+                    mesh.triangles.push(if y > 0.0 {
+                        [center, q, p]
+                    } else {
+                        [center, p, q]
+                    });
+                }
+            }
+        }
+
+        mesh
+    }
+
+    // adds a grid spanning `u` and `v` from `origin`, facing u x v.
+    fn push_grid(
+        &mut self,
+        origin: Tuple,
+        u: Tuple,
+        v: Tuple,
+        u_segments: usize,
+        v_segments: usize,
+    ) {
+        let start = self.positions.len();
+        let normal = u.cross(&v).normalize();
+
+        for i in 0..=u_segments {
+            for j in 0..=v_segments {
+                let (s, t) = (
+                    i as Float / u_segments as Float,
+                    j as Float / v_segments as Float,
+                );
+                self.positions.push(origin + u * s + v * t);
+                self.normals.push(normal);
+                self.uvs.push((s, t));
+            }
+        }
+
+        for i in 0..u_segments {
+            for j in 0..v_segments {
+                let [a, b, c, d] = quad_indices(i, j, v_segments + 1).map(|x| x + start);
+                self.triangles.push([a, b, c]);
+                self.triangles.push([a, c, d]);
+            }
+        }
+    }
+}
+
+// corners of the grid cell at (i, j) in a row-major vertex grid `stride` wide:
+// (i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1).
+fn quad_indices(i: usize, j: usize, stride: usize) -> [usize; 4] {
+    [
+        i * stride + j,
+        (i + 1) * stride + j,
+        (i + 1) * stride + j + 1,
+        i * stride + j + 1,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformation::{scaling, translation};
+
+    fn quad() -> Mesh {
+        Mesh::new(
+            vec![
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::point(1.0, 0.0, 0.0),
+                Tuple::point(1.0, 1.0, 0.0),
+                Tuple::point(0.0, 1.0, 0.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+        )
+    }
+
+    #[test]
+    fn test_mesh() {
+        let mesh = quad();
+
+        assert_eq!(mesh.vertex_count(), 4);
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(
+            mesh.triangle(1),
+            [
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::point(1.0, 1.0, 0.0),
+                Tuple::point(0.0, 1.0, 0.0)
+            ]
+        );
+        assert_eq!(mesh.face_normal(0), Tuple::vector(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "triangle index 3 out of bounds for mesh with 3 vertices!")]
+    fn test_mesh_bad_index() {
+        Mesh::new(vec![Tuple::point(0.0, 0.0, 0.0); 3], vec![[0, 1, 3]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "plane needs at least 1 segment along each axis!")]
+    fn test_plane_no_segments() {
+        Mesh::plane(0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "cube needs at least 1 segment per edge!")]
+    fn test_cube_no_segments() {
+        Mesh::cube(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "uv sphere needs at least 3 segments!")]
+    fn test_uv_sphere_too_few_segments() {
+        Mesh::uv_sphere(2, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "uv sphere needs at least 2 rings!")]
+    fn test_uv_sphere_too_few_rings() {
+        Mesh::uv_sphere(8, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "torus needs at least 3 sides!")]
+    fn test_torus_too_few_sides() {
+        Mesh::torus(1.0, 0.25, 8, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "cylinder needs at least 3 segments!")]
+    fn test_cylinder_too_few_segments() {
+        Mesh::cylinder(0, 1, true);
+    }
+
+    #[test]
+    #[should_panic(expected = "cylinder needs at least 1 height segment!")]
+    fn test_cylinder_no_height_segments() {
+        Mesh::cylinder(8, 0, false);
+    }
+
+    #[test]
+    fn test_transform_mesh() {
+        let mut mesh = quad();
+        mesh.normals = vec![Tuple::vector(0.0, 1.0, 1.0).normalize(); 4];
+
+        let moved = mesh.transform(&(translation(0.0, 0.0, 5.0) * scaling(1.0, 2.0, 1.0)));
+
+        assert_eq!(moved.positions[2], Tuple::point(1.0, 2.0, 5.0));
+        assert_eq!(moved.normals[0], Tuple::vector(0.0, 1.0, 2.0).normalize());
+        assert_eq!(moved.triangles, mesh.triangles);
+    }
+
+    // two faces of a cube sharing the edge from (0, 0, 0) to (0, 1, 0).
+    fn corner() -> Mesh {
+        Mesh::new(
+            vec![
+                Tuple::point(0.0, 0.0, 0.0),
+                Tuple::point(0.0, 1.0, 0.0),
+                Tuple::point(1.0, 0.0, 0.0),
+                Tuple::point(0.0, 0.0, 1.0),
+            ],
+            vec![[0, 1, 2], [0, 3, 1]],
+        )
+    }
+
+    #[test]
+    fn test_smooth_normals_flat() {
+        let mut mesh = quad();
+        mesh.compute_smooth_normals(0.5);
+
+        assert_eq!(mesh.vertex_count(), 4);
+        assert!(mesh
+            .normals
+            .iter()
+            .all(|n| *n == Tuple::vector(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_smooth_normals_across_edge() {
+        let mut mesh = corner();
+        mesh.compute_smooth_normals(crate::consts::PI);
+
+        let diagonal = Tuple::vector(-1.0, 0.0, -1.0).normalize();
+        assert_eq!(mesh.vertex_count(), 4);
+        assert_eq!(mesh.normals[0], diagonal);
+        assert_eq!(mesh.normals[1], diagonal);
+        assert_eq!(mesh.normals[2], Tuple::vector(0.0, 0.0, -1.0));
+        assert_eq!(mesh.normals[3], Tuple::vector(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_smooth_normals_crease() {
+        let mut mesh = corner();
+        mesh.uvs = vec![(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)];
+        mesh.compute_smooth_normals(crate::consts::FRAC_PI_4);
+
+        // the shared edge is split so each face stays flat.
+        assert_eq!(mesh.vertex_count(), 6);
+        assert_eq!(mesh.uvs.len(), 6);
+        for face in 0..2 {
+            let n = mesh.face_normal(face).normalize();
+            for v in mesh.triangles[face] {
+                assert_eq!(mesh.normals[v], n);
+            }
+        }
+        assert_eq!(mesh.triangle(1), corner().triangle(1));
+    }
+
+    // every triangle winds so its face normal agrees with its vertex normals,
+    // and every normal is unit length.
+    fn assert_well_formed(mesh: &Mesh) {
+        assert_eq!(mesh.normals.len(), mesh.vertex_count());
+        assert!(mesh.uvs.is_empty() || mesh.uvs.len() == mesh.vertex_count());
+        assert!(mesh
+            .normals
+            .iter()
+            .all(|n| (n.magnitude() - 1.0).abs() < 1e-4));
+
+        for (i, triangle) in mesh.triangles.iter().enumerate() {
+            let face = mesh.face_normal(i);
+            assert!(face.magnitude() > 0.0, "degenerate triangle {}", i);
+            for v in triangle {
+                assert!(face * mesh.normals[*v] > 0.0, "triangle {} faces inward", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plane_cube() {
+        let plane = Mesh::plane(2, 3);
+        assert_well_formed(&plane);
+        assert_eq!(plane.vertex_count(), 12);
+        assert_eq!(plane.triangle_count(), 12);
+        assert!(plane
+            .normals
+            .iter()
+            .all(|n| *n == Tuple::vector(0.0, 1.0, 0.0)));
+
+        let cube = Mesh::cube(2);
+        assert_well_formed(&cube);
+        assert_eq!(cube.vertex_count(), 6 * 9);
+        assert_eq!(cube.triangle_count(), 6 * 8);
+        assert!(cube
+            .positions
+            .iter()
+            .all(|p| p.x.abs().max(p.y.abs()).max(p.z.abs()) == 1.0));
+    }
+
+    #[test]
+    fn test_spheres() {
+        let uv = Mesh::uv_sphere(8, 4);
+        assert_well_formed(&uv);
+        assert_eq!(uv.vertex_count(), 9 * 5);
+        assert_eq!(uv.triangle_count(), 8 * 2 + 8 * 2 * 2);
+
+        let ico = Mesh::icosphere(2);
+        assert_well_formed(&ico);
+        assert_eq!(ico.vertex_count(), 162);
+        assert_eq!(ico.triangle_count(), 320);
+
+        for mesh in [uv, ico] {
+            assert!(mesh
+                .positions
+                .iter()
+                .all(|p| (Tuple::vector(p.x, p.y, p.z).magnitude() - 1.0).abs() < 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_torus_cylinder() {
+        let torus = Mesh::torus(2.0, 0.5, 12, 6);
+        assert_well_formed(&torus);
+        assert_eq!(torus.triangle_count(), 12 * 6 * 2);
+        assert!(torus.positions.iter().all(|p| p.y.abs() <= 0.5 + 1e-4));
+
+        let open = Mesh::cylinder(6, 2, false);
+        assert_well_formed(&open);
+        assert_eq!(open.triangle_count(), 6 * 2 * 2);
+
+        let capped = Mesh::cylinder(6, 2, true);
+        assert_well_formed(&capped);
+        assert_eq!(capped.triangle_count(), 6 * 2 * 2 + 2 * 6);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{canvas::Canvas, Float};
+
+// thresholds used when checking consecutive animation frames against each other.
+// `max_brightness_jump` is relative to the previous frame's mean luminance,
+// `pixel_threshold` is the luminance change that makes a single pixel count as a
+// spike, and `max_spike_ratio` is the fraction of spiking pixels a frame may have.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameCheck {
+    pub max_brightness_jump: Float,
+    pub pixel_threshold: Float,
+    pub max_spike_ratio: Float,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameReport {
+    pub frame: usize,
+    pub mean_luminance: Float,
+    pub brightness_jump: Float,
+    pub spike_ratio: Float,
+    pub flagged: bool,
+}
+
+impl Default for FrameCheck {
+    fn default() -> Self {
+        Self {
+            max_brightness_jump: 0.25,
+            pixel_threshold: 0.5,
+            max_spike_ratio: 0.01,
+        }
+    }
+}
+
+impl FrameCheck {
+    pub fn run(&self, frames: &[Canvas]) -> Vec<FrameReport> {
+        let mut reports: Vec<FrameReport> = vec![];
+
+        for (i, frame) in frames.iter().enumerate() {
+            let mean_luminance = mean_luminance(frame);
+
+            let report = match i.checked_sub(1).map(|prev| &frames[prev]) {
+                None => FrameReport {
+                    frame: i,
+                    mean_luminance,
+                    brightness_jump: 0.0,
+                    spike_ratio: 0.0,
+                    flagged: false,
+                },
+                Some(prev) => {
+                    if prev.width != frame.width || prev.height != frame.height {
+                        panic!(
+                            "cannot compare frames of different sizes! frame {} is ({}, {}), frame {} is ({}, {})",
+                            i - 1, prev.width, prev.height, i, frame.width, frame.height
+                        );
+                    }
+
+                    let prev_luminance = reports[i - 1].mean_luminance;
+                    let brightness_jump = (mean_luminance - prev_luminance).abs()
+                        / prev_luminance.max(Float::EPSILON);
+
+                    let spikes = frame
+                        .pixels
+                        .iter()
+                        .zip(prev.pixels.iter())
+                        .filter(|(a, b)| {
+                            (a.luminance() - b.luminance()).abs() > self.pixel_threshold
+                        })
+                        .count();
+                    let spike_ratio = spikes as Float / frame.pixels.len().max(1) as Float;
+
+                    FrameReport {
+                        frame: i,
+                        mean_luminance,
+                        brightness_jump,
+                        spike_ratio,
+                        flagged: brightness_jump > self.max_brightness_jump
+                            || spike_ratio > self.max_spike_ratio,
+                    }
+                }
+            };
+
+            reports.push(report);
+        }
+
+        reports
+    }
+}
+
+fn mean_luminance(canvas: &Canvas) -> Float {
+    if canvas.pixels.is_empty() {
+        return 0.0;
+    }
+
+    canvas.pixels.iter().map(|p| p.luminance()).sum::<Float>() / canvas.pixels.len() as Float
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn frame(color: Color) -> Canvas {
+        let mut c = Canvas::new(10, 10);
+        for pixel in c.pixels.iter_mut() {
+            *pixel = color;
+        }
+        c
+    }
+
+    #[test]
+    fn test_steady_frames() {
+        let frames = vec![frame(Color::new(0.5, 0.5, 0.5)); 3];
+        let reports = FrameCheck::default().run(&frames);
+
+        assert_eq!(reports.len(), 3);
+        assert!(reports.iter().all(|r| !r.flagged));
+        assert_eq!(reports[1].brightness_jump, 0.0);
+    }
+
+    #[test]
+    fn test_brightness_jump() {
+        let frames = vec![
+            frame(Color::new(0.5, 0.5, 0.5)),
+            frame(Color::new(0.5, 0.5, 0.5)),
+            frame(Color::new(0.0, 0.0, 0.0)),
+        ];
+        let reports = FrameCheck::default().run(&frames);
+
+        assert!(!reports[1].flagged);
+        assert!(reports[2].flagged);
+        assert_eq!(reports[2].brightness_jump, 1.0);
+    }
+
+    #[test]
+    fn test_noise_spikes() {
+        let a = frame(Color::new(0.2, 0.2, 0.2));
+        let mut b = a.clone();
+        b.write_pixel(3, 3, Color::new(10.0, 10.0, 10.0));
+        b.write_pixel(7, 1, Color::new(10.0, 10.0, 10.0));
+
+        let check = FrameCheck {
+            max_brightness_jump: Float::INFINITY,
+            ..FrameCheck::default()
+        };
+        let reports = check.run(&[a, b]);
+
+        assert_eq!(reports[1].spike_ratio, 0.02);
+        assert!(reports[1].flagged);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot compare frames of different sizes!")]
+    fn test_frame_size_mismatch() {
+        FrameCheck::default().run(&[Canvas::new(2, 2), Canvas::new(3, 2)]);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{consts::PI, tuple::Tuple, Float};
+
+// source of uniform random numbers for the samplers below. kept as a small
+// trait so callers can bring their own generator.
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    // uniform in [0, 1). keeps as many top bits as the mantissa holds (53, or
+    // 24 with the `f32` feature) so the result is exact and can't round up to
+    // 1.0.
+    #[cfg(not(feature = "f32"))]
+    fn next_float(&mut self) -> Float {
+        (self.next_u64() >> 11) as Float * (1.0 / (1u64 << 53) as Float)
+    }
+
+    #[cfg(feature = "f32")]
+    fn next_float(&mut self) -> Float {
+        (self.next_u64() >> 40) as Float * (1.0 / (1u64 << 24) as Float)
+    }
+}
+
+// xorshift64*, small and fast enough for sampling. not suitable for anything
+// that needs real randomness.
+#[derive(Clone, Debug)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        // a zero state would only ever produce zeros.
+        Self {
+            state: if seed == 0 {
+                0x9e37_79b9_7f4a_7c15
+            } else {
+                seed
+            },
+        }
+    }
+}
+
+impl Rng for XorShift {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl Tuple {
+    // uniformly distributed over the unit sphere.
+    pub fn random_unit_vector(rng: &mut impl Rng) -> Self {
+        let z = 1.0 - 2.0 * rng.next_float();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.next_float();
+
+        Tuple::vector(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    // uniformly distributed over the hemisphere around `normal`.
+    pub fn random_in_hemisphere(normal: Tuple, rng: &mut impl Rng) -> Self {
+        let v = Tuple::random_unit_vector(rng);
+        if v * normal < 0.0 {
+            -v
+        } else {
+            v
+        }
+    }
+
+    // cosine-weighted over the hemisphere around `normal`, which is expected to
+    // be normalized.
+    pub fn random_cosine_hemisphere(normal: Tuple, rng: &mut impl Rng) -> Self {
+        let r2 = rng.next_float();
+        let r = r2.sqrt();
+        let phi = 2.0 * PI * rng.next_float();
+
+        let helper = if normal.x.abs() > 0.9 {
+            Tuple::vector(0.0, 1.0, 0.0)
+        } else {
+            Tuple::vector(1.0, 0.0, 0.0)
+        };
+        let tangent = helper.cross(&normal).normalize();
+        let bitangent = normal.cross(&tangent);
+
+        tangent * (r * phi.cos()) + bitangent * (r * phi.sin()) + normal * (1.0 - r2).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_xorshift() {
+        let mut a = XorShift::new(42);
+        let mut b = XorShift::new(42);
+
+        for _ in 0..100 {
+            let x = a.next_float();
+            assert_eq!(x, b.next_float());
+            assert!((0.0..1.0).contains(&x));
+        }
+        assert_ne!(XorShift::new(0).next_u64(), 0);
+    }
+
+    struct Fixed(u64);
+
+    impl Rng for Fixed {
+        fn next_u64(&mut self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_next_float_range() {
+        assert_eq!(Fixed(0).next_float(), 0.0);
+        assert!(Fixed(u64::MAX).next_float() < 1.0);
+        assert_eq!(Fixed(1 << 63).next_float(), 0.5);
+    }
+
+    #[cfg(not(feature = "f32"))]
+    #[test]
+    fn test_next_float_resolution() {
+        // bits below the top 24 still show up in an f64 sample.
+        assert_ne!(Fixed(1 << 11).next_float(), 0.0);
+
+        let mut rng = XorShift::new(7);
+        let mut samples: Vec<_> = (0..100_000).map(|_| rng.next_float().to_bits()).collect();
+        samples.sort_unstable();
+        samples.dedup();
+        assert_eq!(samples.len(), 100_000);
+    }
+
+    #[test]
+    fn test_random_unit_vector() {
+        let mut rng = XorShift::new(1);
+
+        for _ in 0..1000 {
+            let v = Tuple::random_unit_vector(&mut rng);
+            assert!(v.is_vector());
+            assert_float_eq!(v.magnitude(), 1.0, abs <= approx::DEFAULT_EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_random_in_hemisphere() {
+        let mut rng = XorShift::new(2);
+        let normal = Tuple::vector(0.0, 1.0, 0.0);
+
+        for _ in 0..1000 {
+            let v = Tuple::random_in_hemisphere(normal, &mut rng);
+            assert!(v * normal >= 0.0);
+        }
+    }
+
+    #[test]
+    fn test_random_cosine_hemisphere() {
+        let mut rng = XorShift::new(3);
+        let normal = Tuple::vector(1.0, 1.0, 0.0).normalize();
+        let samples = 10000;
+        let mut sum = 0.0;
+
+        for _ in 0..samples {
+            let v = Tuple::random_cosine_hemisphere(normal, &mut rng);
+            assert_float_eq!(v.magnitude(), 1.0, abs <= approx::DEFAULT_EPSILON);
+            assert!(v * normal >= 0.0);
+            sum += v * normal;
+        }
+
+        // E[cos] is 2/3 for a cosine-weighted hemisphere.
+        assert_float_eq!(sum / samples as Float, 2.0 / 3.0, abs <= 0.01);
+    }
+}
@@ -0,0 +1,363 @@
+// 4-wide helpers backing Tuple arithmetic and 4x4 matrix products.
+// with the `simd` feature on x86_64 these use SSE2 (two f64 lanes or four f32
+// lanes per register), otherwise they fall back to plain scalar code. all paths
+// accumulate in the same order, so results are bit-for-bit identical.
+
+#[cfg(all(feature = "simd", target_arch = "x86_64", not(feature = "f32")))]
+// sse2 is part of the x86_64 baseline, so every intrinsic used here exists on
+// any cpu this module can be compiled for. the only other thing the unsafe
+// blocks rely on is that loads and stores stay in bounds.
+mod imp {
+    use std::arch::x86_64::*;
+
+    // two unaligned 2-lane reads at 0 and 2 of a 4 element array. loadu has
+    // no alignment requirement.
+    #[inline]
+    fn load(a: &[f64; 4]) -> (__m128d, __m128d) {
+        unsafe { (_mm_loadu_pd(a.as_ptr()), _mm_loadu_pd(a.as_ptr().add(2))) }
+    }
+
+    // the mirror of `load`, storeu into a local 4 element array.
+    #[inline]
+    fn store(lo: __m128d, hi: __m128d) -> [f64; 4] {
+        let mut out = [0.0; 4];
+        unsafe {
+            _mm_storeu_pd(out.as_mut_ptr(), lo);
+            _mm_storeu_pd(out.as_mut_ptr().add(2), hi);
+        }
+        out
+    }
+
+    #[inline]
+    pub fn add4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+        let (a0, a1) = load(&a);
+        let (b0, b1) = load(&b);
+        unsafe { store(_mm_add_pd(a0, b0), _mm_add_pd(a1, b1)) }
+    }
+
+    #[inline]
+    pub fn sub4(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+        let (a0, a1) = load(&a);
+        let (b0, b1) = load(&b);
+        unsafe { store(_mm_sub_pd(a0, b0), _mm_sub_pd(a1, b1)) }
+    }
+
+    #[inline]
+    pub fn neg4(a: [f64; 4]) -> [f64; 4] {
+        let (a0, a1) = load(&a);
+        unsafe {
+            let sign = _mm_set1_pd(-0.0);
+            store(_mm_xor_pd(a0, sign), _mm_xor_pd(a1, sign))
+        }
+    }
+
+    #[inline]
+    pub fn scale4(a: [f64; 4], s: f64) -> [f64; 4] {
+        let (a0, a1) = load(&a);
+        unsafe {
+            let s = _mm_set1_pd(s);
+            store(_mm_mul_pd(a0, s), _mm_mul_pd(a1, s))
+        }
+    }
+
+    #[inline]
+    pub fn div4(a: [f64; 4], s: f64) -> [f64; 4] {
+        let (a0, a1) = load(&a);
+        unsafe {
+            let s = _mm_set1_pd(s);
+            store(_mm_div_pd(a0, s), _mm_div_pd(a1, s))
+        }
+    }
+
+    #[inline]
+    pub fn dot4(a: [f64; 4], b: [f64; 4]) -> f64 {
+        let (a0, a1) = load(&a);
+        let (b0, b1) = load(&b);
+        let p = unsafe { store(_mm_mul_pd(a0, b0), _mm_mul_pd(a1, b1)) };
+        p[0] + p[1] + p[2] + p[3]
+    }
+
+    #[inline]
+    pub fn mul_mat4(a: &[f64], b: &[f64]) -> [f64; 16] {
+        // every unaligned access below is within the first 16 elements of a,
+        // b and out.
+        assert!(a.len() >= 16 && b.len() >= 16);
+
+        let mut out = [0.0; 16];
+        for row in 0..4 {
+            unsafe {
+                let mut lo = _mm_setzero_pd();
+                let mut hi = _mm_setzero_pd();
+                for k in 0..4 {
+                    let s = _mm_set1_pd(a[row * 4 + k]);
+                    lo = _mm_add_pd(lo, _mm_mul_pd(s, _mm_loadu_pd(b.as_ptr().add(k * 4))));
+                    hi = _mm_add_pd(hi, _mm_mul_pd(s, _mm_loadu_pd(b.as_ptr().add(k * 4 + 2))));
+                }
+                _mm_storeu_pd(out.as_mut_ptr().add(row * 4), lo);
+                _mm_storeu_pd(out.as_mut_ptr().add(row * 4 + 2), hi);
+            }
+        }
+
+        out
+    }
+
+    #[inline]
+    pub fn mul_mat4_vec4(m: &[f64], v: [f64; 4]) -> [f64; 4] {
+        // only indexes m through bounds-checked slicing, the rest stays in
+        // registers.
+        assert!(m.len() >= 16);
+
+        unsafe {
+            let mut lo = _mm_setzero_pd();
+            let mut hi = _mm_setzero_pd();
+            for (k, x) in v.iter().enumerate() {
+                let s = _mm_set1_pd(*x);
+                lo = _mm_add_pd(lo, _mm_mul_pd(_mm_set_pd(m[4 + k], m[k]), s));
+                hi = _mm_add_pd(hi, _mm_mul_pd(_mm_set_pd(m[12 + k], m[8 + k]), s));
+            }
+            store(lo, hi)
+        }
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64", feature = "f32"))]
+// sse2 is part of the x86_64 baseline, so every intrinsic used here exists on
+// any cpu this module can be compiled for. the only other thing the unsafe
+// blocks rely on is that loads and stores stay in bounds.
+mod imp {
+    use std::arch::x86_64::*;
+
+    // one unaligned 4-lane read of a 4 element array. loadu has no alignment
+    // requirement.
+    #[inline]
+    fn load(a: &[f32; 4]) -> __m128 {
+        unsafe { _mm_loadu_ps(a.as_ptr()) }
+    }
+
+    // the mirror of `load`, storeu into a local 4 element array.
+    #[inline]
+    fn store(v: __m128) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        unsafe { _mm_storeu_ps(out.as_mut_ptr(), v) };
+        out
+    }
+
+    #[inline]
+    pub fn add4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_add_ps(load(&a), load(&b))) }
+    }
+
+    #[inline]
+    pub fn sub4(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_sub_ps(load(&a), load(&b))) }
+    }
+
+    #[inline]
+    pub fn neg4(a: [f32; 4]) -> [f32; 4] {
+        unsafe { store(_mm_xor_ps(load(&a), _mm_set1_ps(-0.0))) }
+    }
+
+    #[inline]
+    pub fn scale4(a: [f32; 4], s: f32) -> [f32; 4] {
+        unsafe { store(_mm_mul_ps(load(&a), _mm_set1_ps(s))) }
+    }
+
+    #[inline]
+    pub fn div4(a: [f32; 4], s: f32) -> [f32; 4] {
+        unsafe { store(_mm_div_ps(load(&a), _mm_set1_ps(s))) }
+    }
+
+    #[inline]
+    pub fn dot4(a: [f32; 4], b: [f32; 4]) -> f32 {
+        let p = unsafe { store(_mm_mul_ps(load(&a), load(&b))) };
+        p[0] + p[1] + p[2] + p[3]
+    }
+
+    #[inline]
+    pub fn mul_mat4(a: &[f32], b: &[f32]) -> [f32; 16] {
+        // every unaligned access below is within the first 16 elements of a,
+        // b and out.
+        assert!(a.len() >= 16 && b.len() >= 16);
+
+        let mut out = [0.0; 16];
+        for row in 0..4 {
+            unsafe {
+                let mut sum = _mm_setzero_ps();
+                for k in 0..4 {
+                    let s = _mm_set1_ps(a[row * 4 + k]);
+                    sum = _mm_add_ps(sum, _mm_mul_ps(s, _mm_loadu_ps(b.as_ptr().add(k * 4))));
+                }
+                _mm_storeu_ps(out.as_mut_ptr().add(row * 4), sum);
+            }
+        }
+
+        out
+    }
+
+    #[inline]
+    pub fn mul_mat4_vec4(m: &[f32], v: [f32; 4]) -> [f32; 4] {
+        // only indexes m through bounds-checked slicing, the rest stays in
+        // registers.
+        assert!(m.len() >= 16);
+
+        unsafe {
+            let mut sum = _mm_setzero_ps();
+            for (k, x) in v.iter().enumerate() {
+                let column = _mm_set_ps(m[12 + k], m[8 + k], m[4 + k], m[k]);
+                sum = _mm_add_ps(sum, _mm_mul_ps(column, _mm_set1_ps(*x)));
+            }
+            store(sum)
+        }
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod imp {
+    use crate::Float;
+
+    #[inline]
+    pub fn add4(a: [Float; 4], b: [Float; 4]) -> [Float; 4] {
+        [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]
+    }
+
+    #[inline]
+    pub fn sub4(a: [Float; 4], b: [Float; 4]) -> [Float; 4] {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+    }
+
+    #[inline]
+    pub fn neg4(a: [Float; 4]) -> [Float; 4] {
+        [-a[0], -a[1], -a[2], -a[3]]
+    }
+
+    #[inline]
+    pub fn scale4(a: [Float; 4], s: Float) -> [Float; 4] {
+        [a[0] * s, a[1] * s, a[2] * s, a[3] * s]
+    }
+
+    #[inline]
+    pub fn div4(a: [Float; 4], s: Float) -> [Float; 4] {
+        [a[0] / s, a[1] / s, a[2] / s, a[3] / s]
+    }
+
+    #[inline]
+    pub fn dot4(a: [Float; 4], b: [Float; 4]) -> Float {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+    }
+
+    #[inline]
+    pub fn mul_mat4(a: &[Float], b: &[Float]) -> [Float; 16] {
+        let mut out = [0.0; 16];
+        for row in 0..4 {
+            for col in 0..4 {
+                let mut sum = 0.0;
+                for k in 0..4 {
+                    sum += a[row * 4 + k] * b[k * 4 + col];
+                }
+                out[row * 4 + col] = sum;
+            }
+        }
+
+        out
+    }
+
+    #[inline]
+    pub fn mul_mat4_vec4(m: &[Float], v: [Float; 4]) -> [Float; 4] {
+        let mut out = [0.0; 4];
+        for (row, x) in out.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            for (k, y) in v.iter().enumerate() {
+                sum += m[row * 4 + k] * y;
+            }
+            *x = sum;
+        }
+
+        out
+    }
+}
+
+pub use imp::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuple_ops() {
+        let a = [1.0, -2.0, 3.0, -4.0];
+        let b = [0.5, 0.25, -1.0, 2.0];
+
+        assert_eq!(add4(a, b), [1.5, -1.75, 2.0, -2.0]);
+        assert_eq!(sub4(a, b), [0.5, -2.25, 4.0, -6.0]);
+        assert_eq!(neg4(a), [-1.0, 2.0, -3.0, 4.0]);
+        assert_eq!(scale4(a, 2.0), [2.0, -4.0, 6.0, -8.0]);
+        assert_eq!(div4(a, 2.0), [0.5, -1.0, 1.5, -2.0]);
+        assert_eq!(dot4(a, b), -11.0);
+    }
+
+    #[test]
+    fn test_mul_mat4() {
+        let a = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0, 3.0, 2.0,
+        ];
+        let b = [
+            -2.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, -1.0, 4.0, 3.0, 6.0, 5.0, 1.0, 2.0, 7.0, 8.0,
+        ];
+
+        assert_eq!(
+            mul_mat4(&a, &b),
+            [
+                20.0, 22.0, 50.0, 48.0, 44.0, 54.0, 114.0, 108.0, 40.0, 58.0, 110.0, 102.0, 16.0,
+                26.0, 46.0, 42.0,
+            ]
+        );
+    }
+
+    // cargo test --release --features simd -- --ignored --nocapture, then the
+    // same without the feature, to compare against the scalar fallback.
+    #[test]
+    #[ignore]
+    fn bench_mul_mat4() {
+        use crate::Float;
+        use std::{hint::black_box, time::Instant};
+
+        let mut a = [0.0 as Float; 16];
+        for (i, x) in a.iter_mut().enumerate() {
+            *x = i as Float * 0.01;
+        }
+        let iterations = 10_000_000;
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            black_box(mul_mat4(black_box(&a), black_box(&a)));
+        }
+        let matrices = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..iterations {
+            black_box(mul_mat4_vec4(
+                black_box(&a),
+                black_box([1.0, 2.0, 3.0, 1.0]),
+            ));
+        }
+        let vectors = start.elapsed();
+
+        println!(
+            "mul_mat4: {:.2} ns, mul_mat4_vec4: {:.2} ns",
+            matrices.as_nanos() as f64 / iterations as f64,
+            vectors.as_nanos() as f64 / iterations as f64
+        );
+    }
+
+    #[test]
+    fn test_mul_mat4_vec4() {
+        let m = [
+            1.0, 2.0, 3.0, 4.0, 2.0, 4.0, 4.0, 2.0, 8.0, 6.0, 4.0, 1.0, 0.0, 0.0, 0.0, 1.0,
+        ];
+
+        assert_eq!(
+            mul_mat4_vec4(&m, [1.0, 2.0, 3.0, 1.0]),
+            [18.0, 24.0, 33.0, 1.0]
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{color::Color, consts::PI, tuple::Tuple, Float};
+
+// analytic daylight sky from preetham, shirley and smits (1999). radiance is
+// in kcd/m^2 converted to linear srgb, so expect values well above 1.0 and use
+// `Color::exposure` and tone mapping before writing images.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PreethamSky {
+    // sun angle above the horizon and around y from +x towards +z, in radians.
+    pub sun_elevation: Float,
+    pub sun_azimuth: Float,
+    // haziness of the atmosphere, the model is fitted for roughly 2 to 10.
+    pub turbidity: Float,
+}
+
+// luminance of the solar disc above the atmosphere, in the sky's kcd/m^2.
+const SUN_LUMINANCE: Float = 1.6e6;
+
+// wavelengths in micrometres the sun's transmittance is evaluated at for the
+// red, green and blue channels.
+const WAVELENGTHS: [Float; 3] = [0.680, 0.550, 0.440];
+
+// coefficients of the perez distribution function for one channel.
+#[derive(Clone, Copy, Debug)]
+struct Perez {
+    a: Float,
+    b: Float,
+    c: Float,
+    d: Float,
+    e: Float,
+}
+
+impl Perez {
+    fn eval(&self, cos_theta: Float, gamma: Float) -> Float {
+        let cos_gamma = gamma.cos();
+        (1.0 + self.a * (self.b / cos_theta).exp())
+            * (1.0 + self.c * (self.d * gamma).exp() + self.e * cos_gamma * cos_gamma)
+    }
+}
+
+impl Default for PreethamSky {
+    fn default() -> Self {
+        Self {
+            sun_elevation: PI / 4.0,
+            sun_azimuth: 0.0,
+            turbidity: 3.0,
+        }
+    }
+}
+
+impl PreethamSky {
+    // unit vector pointing towards the sun, e.g. for a directional light.
+    pub fn sun_direction(&self) -> Tuple {
+        let (sin_e, cos_e) = self.sun_elevation.sin_cos();
+        let (sin_a, cos_a) = self.sun_azimuth.sin_cos();
+        Tuple::vector(cos_e * cos_a, sin_e, cos_e * sin_a)
+    }
+
+    // fraction of sunlight reaching the ground per channel, from preetham's
+    // rayleigh and aerosol transmittance (ozone and water vapour are left out).
+    // white at the zenith on a clear day, redder and darker towards the
+    // horizon and with more haze. use it to tint a directional light.
+    pub fn sun_color(&self) -> Color {
+        if self.sun_elevation <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let theta_sun = PI / 2.0 - self.sun_elevation.min(PI / 2.0);
+        // relative optical mass of the air along the sun ray, kasten's formula.
+        let mass = 1.0 / (theta_sun.cos() + 0.15 * (93.885 - theta_sun.to_degrees()).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+
+        let [r, g, b] = WAVELENGTHS.map(|lambda| {
+            let rayleigh = (-0.008735 * mass * lambda.powf(-4.08)).exp();
+            let aerosol = (-beta * mass * lambda.powf(-1.3)).exp();
+            rayleigh * aerosol
+        });
+        Color::new(r, g, b)
+    }
+
+    // radiance of the solar disc, in the same units as `radiance`.
+    pub fn sun_radiance(&self) -> Color {
+        self.sun_color() * SUN_LUMINANCE
+    }
+
+    // sky radiance seen looking along `direction`. the model only describes
+    // the upper hemisphere, so anything at or below the horizon is black.
+    pub fn radiance(&self, direction: Tuple) -> Color {
+        let direction = direction.normalize();
+        if direction.y <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let t = self.turbidity;
+        let theta_sun = PI / 2.0 - self.sun_elevation.clamp(0.0, PI / 2.0);
+        let gamma = direction.angle_between(&self.sun_direction());
+        let cos_theta = direction.y;
+
+        let luminance = Perez {
+            a: 0.1787 * t - 1.4630,
+            b: -0.3554 * t + 0.4275,
+            c: -0.0227 * t + 5.3251,
+            d: 0.1206 * t - 2.5771,
+            e: -0.0670 * t + 0.3703,
+        };
+        let x = Perez {
+            a: -0.0193 * t - 0.2592,
+            b: -0.0665 * t + 0.0008,
+            c: -0.0004 * t + 0.2125,
+            d: -0.0641 * t - 0.8989,
+            e: -0.0033 * t + 0.0452,
+        };
+        let y = Perez {
+            a: -0.0167 * t - 0.2608,
+            b: -0.0950 * t + 0.0092,
+            c: -0.0079 * t + 0.2102,
+            d: -0.0441 * t - 1.6537,
+            e: -0.0109 * t + 0.0529,
+        };
+
+        let (zenith_luminance, zenith_x, zenith_y) = self.zenith(theta_sun);
+        let relative = |p: &Perez| p.eval(cos_theta, gamma) / p.eval(1.0, theta_sun);
+
+        xyy_to_rgb(
+            zenith_x * relative(&x),
+            zenith_y * relative(&y),
+            zenith_luminance * relative(&luminance),
+        )
+    }
+
+    // luminance and chromaticity straight up, for the sun at `theta_sun` from
+    // the zenith.
+    fn zenith(&self, theta_sun: Float) -> (Float, Float, Float) {
+        let t = self.turbidity;
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+
+        let powers = [theta_sun.powi(3), theta_sun.powi(2), theta_sun, 1.0];
+        let poly =
+            |c: [Float; 4]| -> Float { c.iter().zip(powers.iter()).map(|(c, p)| c * p).sum() };
+
+        let x = t * t * poly([0.00166, -0.00375, 0.00209, 0.0])
+            + t * poly([-0.02903, 0.06377, -0.03202, 0.00394])
+            + poly([0.11693, -0.21196, 0.06052, 0.25886]);
+        let y = t * t * poly([0.00275, -0.00610, 0.00317, 0.0])
+            + t * poly([-0.04214, 0.08970, -0.04153, 0.00516])
+            + poly([0.15346, -0.26756, 0.06670, 0.26688]);
+
+        (luminance, x, y)
+    }
+}
+
+// cie xyY to linear srgb (d65).
+fn xyy_to_rgb(x: Float, y: Float, luminance: Float) -> Color {
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+
+    Color::new(
+        3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z,
+        -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z,
+        0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z,
+    )
+    .max(&Color::new(0.0, 0.0, 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_sun_direction() {
+        let sky = PreethamSky {
+            sun_elevation: PI / 2.0,
+            ..Default::default()
+        };
+        assert_eq!(sky.sun_direction(), Tuple::vector(0.0, 1.0, 0.0));
+
+        let sky = PreethamSky {
+            sun_elevation: 0.0,
+            sun_azimuth: PI / 2.0,
+            ..Default::default()
+        };
+        assert_eq!(sky.sun_direction(), Tuple::vector(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_zenith_radiance() {
+        let sky = PreethamSky::default();
+        let (luminance, _, _) = sky.zenith(PI / 2.0 - sky.sun_elevation);
+
+        // looking straight up the perez terms cancel out.
+        let zenith = sky.radiance(Tuple::vector(0.0, 1.0, 0.0));
+        assert_float_eq!(zenith.luminance(), luminance, rmax <= 0.01);
+        // a clear sky is blue.
+        assert!(zenith.b > zenith.r);
+    }
+
+    #[test]
+    fn test_sky_radiance() {
+        let sky = PreethamSky::default();
+        let towards_sun = sky.radiance(sky.sun_direction());
+        let away = sky.radiance(Tuple::vector(-1.0, 1.0, 0.0));
+
+        assert!(towards_sun.luminance() > away.luminance());
+        assert_eq!(
+            sky.radiance(Tuple::vector(0.0, -1.0, 0.0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+
+        let hazy = PreethamSky {
+            turbidity: 8.0,
+            ..sky
+        };
+        let hazy_zenith = hazy.radiance(Tuple::vector(0.0, 1.0, 0.0));
+        let clear_zenith = sky.radiance(Tuple::vector(0.0, 1.0, 0.0));
+        assert!(hazy_zenith.b / hazy_zenith.r < clear_zenith.b / clear_zenith.r);
+    }
+
+    #[test]
+    fn test_sun_color() {
+        let high = PreethamSky {
+            sun_elevation: PI / 2.0,
+            ..Default::default()
+        };
+        let low = PreethamSky {
+            sun_elevation: 0.05,
+            ..Default::default()
+        };
+
+        let overhead = high.sun_color();
+        assert!(overhead.r <= 1.0 && overhead.r > 0.8);
+        assert!(overhead.b < overhead.r);
+
+        // sunsets are darker and redder.
+        let sunset = low.sun_color();
+        assert!(sunset.luminance() < overhead.luminance());
+        assert!(sunset.b / sunset.r < overhead.b / overhead.r);
+
+        let hazy = PreethamSky {
+            turbidity: 8.0,
+            ..high
+        };
+        assert!(hazy.sun_color().luminance() < overhead.luminance());
+
+        let night = PreethamSky {
+            sun_elevation: -0.1,
+            ..Default::default()
+        };
+        assert_eq!(night.sun_radiance(), Color::new(0.0, 0.0, 0.0));
+
+        // the disc outshines the sky around it by orders of magnitude.
+        let sky = PreethamSky::default();
+        assert!(
+            sky.sun_radiance().luminance() > 1000.0 * sky.radiance(sky.sun_direction()).luminance()
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use crate::{
+    canvas::Canvas,
+    color::Color,
+    error::{Error, Result},
+    Float,
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+// how `Texture::sample` reads texels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureFilter {
+    // closest texel from the closest mip level.
+    Nearest,
+    // blend of the four closest texels from the closest mip level.
+    Bilinear,
+    // bilinear in the two mip levels around the level of detail, blended
+    // between them.
+    Trilinear,
+}
+
+// an image with its full mip chain, sampled with repeating uv coordinates.
+// u runs left to right and v bottom to top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    // levels[0] is the source image, each following level is half the size of
+    // the one before, down to 1x1.
+    pub levels: Vec<Canvas>,
+    // added to the computed level of detail. positive values pick smaller,
+    // blurrier levels.
+    pub bias: Float,
+    pub filter: TextureFilter,
+}
+
+impl Texture {
+    pub fn new(image: Canvas) -> Self {
+        assert!(
+            image.width > 0 && image.height > 0,
+            "cannot build a texture from an empty canvas!"
+        );
+
+        let mut levels = vec![image];
+
+        loop {
+            let last = levels.last().unwrap();
+            if last.width <= 1 && last.height <= 1 {
+                break;
+            }
+            let next = downsample(last);
+            levels.push(next);
+        }
+
+        Self {
+            levels,
+            bias: 0.0,
+            filter: TextureFilter::Nearest,
+        }
+    }
+
+    // a texture loaded from a ppm file, see `Canvas::from_ppm`.
+    pub fn load(path: &Path) -> Result<Self> {
+        check_format(path)?;
+        let f = File::open(path)?;
+        let image = Canvas::from_ppm(BufReader::new(f))?;
+        if image.width == 0 || image.height == 0 {
+            return Err(Error::EmptyImage);
+        }
+
+        Ok(Self::new(image))
+    }
+
+    // mip level for a sample that covers `footprint` of the texture in uv
+    // units, e.g. the uv distance between neighbouring camera rays.
+    pub fn level_of_detail(&self, footprint: Float) -> Float {
+        let base = &self.levels[0];
+        let texels = footprint * base.width.max(base.height) as Float;
+        let lod = texels.max(Float::MIN_POSITIVE).log2() + self.bias;
+
+        lod.clamp(0.0, (self.levels.len() - 1) as Float)
+    }
+
+    // color at `u`, `v` for a sample covering `footprint`, read with `filter`.
+    pub fn sample(&self, u: Float, v: Float, footprint: Float) -> Color {
+        let lod = self.level_of_detail(footprint);
+
+        match self.filter {
+            TextureFilter::Nearest => nearest(&self.levels[lod.round() as usize], u, v),
+            TextureFilter::Bilinear => bilinear(&self.levels[lod.round() as usize], u, v),
+            TextureFilter::Trilinear => {
+                let fine = lod.floor() as usize;
+                let coarse = (fine + 1).min(self.levels.len() - 1);
+                Color::lerp(
+                    bilinear(&self.levels[fine], u, v),
+                    bilinear(&self.levels[coarse], u, v),
+                    lod - fine as Float,
+                )
+            }
+        }
+    }
+}
+
+fn check_format(path: &Path) -> Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if extension != "ppm" {
+        return Err(Error::UnsupportedFormat(extension));
+    }
+
+    Ok(())
+}
+
+// next mip level, half the size rounded down. each texel is the area-weighted
+// average of the source texels it covers, so odd sizes still see every one.
+fn downsample(level: &Canvas) -> Canvas {
+    let width = (level.width / 2).max(1);
+    let height = (level.height / 2).max(1);
+    let columns = box_weights(level.width, width);
+    let rows = box_weights(level.height, height);
+
+    let mut next = Canvas::new(width, height);
+    if level.alpha.is_some() {
+        next.alpha = Some(vec![0.0; width * height]);
+    }
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, column) in columns.iter().enumerate() {
+            let mut color = Color::new(0.0, 0.0, 0.0);
+            let mut alpha = 0.0;
+            for &(sy, wy) in row {
+                for &(sx, wx) in column {
+                    color += level[(sx, sy)] * (wx * wy);
+                    alpha += level.alpha_at(sx, sy) * wx * wy;
+                }
+            }
+
+            next.pixels[x + y * width] = color;
+            if let Some(a) = &mut next.alpha {
+                a[x + y * width] = alpha;
+            }
+        }
+    }
+
+    next
+}
+
+// for each of `to` output texels, the source texels overlapping its share of
+// `from` along one axis and their normalized weights.
+fn box_weights(from: usize, to: usize) -> Vec<Vec<(usize, Float)>> {
+    let scale = from as Float / to as Float;
+
+    (0..to)
+        .map(|i| {
+            let (start, end) = (i as Float * scale, (i + 1) as Float * scale);
+            (start.floor() as usize..(end.ceil() as usize).min(from))
+                .map(|j| {
+                    let overlap = end.min((j + 1) as Float) - start.max(j as Float);
+                    (j, overlap / scale)
+                })
+                .filter(|(_, w)| *w > 0.0)
+                .collect()
+        })
+        .collect()
+}
+
+fn nearest(level: &Canvas, u: Float, v: Float) -> Color {
+    let x = (u.rem_euclid(1.0) * level.width as Float) as usize;
+    let y = ((1.0 - v.rem_euclid(1.0)) * level.height as Float) as usize;
+
+    // rem_euclid can round up to exactly 1.0 for tiny negative inputs.
+    level[(x.min(level.width - 1), y.min(level.height - 1))]
+}
+
+// texel centers sit at half-integer coordinates, and taps past an edge wrap
+// around to the other side like the uvs do.
+fn bilinear(level: &Canvas, u: Float, v: Float) -> Color {
+    let sx = u.rem_euclid(1.0) * level.width as Float - 0.5;
+    let sy = (1.0 - v.rem_euclid(1.0)) * level.height as Float - 0.5;
+    let (fx, fy) = (sx.floor(), sy.floor());
+    let (tx, ty) = (sx - fx, sy - fy);
+
+    let wrap = |i: Float, size: usize| (i as isize).rem_euclid(size as isize) as usize;
+    let (x0, x1) = (wrap(fx, level.width), wrap(fx + 1.0, level.width));
+    let (y0, y1) = (wrap(fy, level.height), wrap(fy + 1.0, level.height));
+
+    let top = Color::lerp(level[(x0, y0)], level[(x1, y0)], tx);
+    let bottom = Color::lerp(level[(x0, y1)], level[(x1, y1)], tx);
+    Color::lerp(top, bottom, ty)
+}
+
+// loads each texture file once and hands out shared references to it.
+#[derive(Debug, Default)]
+pub struct TextureCache {
+    textures: HashMap<PathBuf, Arc<Texture>>,
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // files are keyed by their canonical path, so different spellings of the
+    // same path share one texture.
+    pub fn load(&mut self, path: &Path) -> Result<Arc<Texture>> {
+        check_format(path)?;
+        let path = std::fs::canonicalize(path)?;
+        if let Some(texture) = self.textures.get(&path) {
+            return Ok(Arc::clone(texture));
+        }
+
+        let texture = Arc::new(Texture::load(&path)?);
+        self.textures.insert(path, Arc::clone(&texture));
+
+        Ok(texture)
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approx;
+    use float_eq::assert_float_eq;
+
+    fn checkers(size: usize) -> Canvas {
+        Canvas::from_fn(size, size, |x, y| {
+            if (x + y) % 2 == 0 {
+                Color::new(1.0, 1.0, 1.0)
+            } else {
+                Color::new(0.0, 0.0, 0.0)
+            }
+        })
+    }
+
+    #[test]
+    fn test_mip_levels() {
+        let texture = Texture::new(checkers(8));
+        let sizes: Vec<_> = texture.levels.iter().map(|l| (l.width, l.height)).collect();
+        assert_eq!(sizes, vec![(8, 8), (4, 4), (2, 2), (1, 1)]);
+
+        // every 2x2 block of a checkerboard averages to grey.
+        for level in &texture.levels[1..] {
+            assert!(level.pixels().all(|&p| p == Color::new(0.5, 0.5, 0.5)));
+        }
+
+        let sizes: Vec<_> = Texture::new(Canvas::new(4, 1))
+            .levels
+            .iter()
+            .map(|l| (l.width, l.height))
+            .collect();
+        assert_eq!(sizes, vec![(4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn test_mip_levels_odd_size() {
+        let mut image = Canvas::new(3, 3);
+        image.fill(Color::new(1.0, 1.0, 1.0));
+        image.write_pixel(1, 1, Color::new(0.0, 0.0, 0.0));
+
+        // every texel counts, including the center one a 2:1 tap skips over.
+        let texture = Texture::new(image);
+        assert_eq!(texture.levels.len(), 2);
+        assert_eq!(
+            texture.levels[1][(0, 0)],
+            Color::new(8.0 / 9.0, 8.0 / 9.0, 8.0 / 9.0)
+        );
+
+        // 5 -> 2 splits the middle column between both outputs.
+        let image = Canvas::from_fn(5, 1, |x, _| {
+            let v = x as Float;
+            Color::new(v, v, v)
+        });
+        let level = &Texture::new(image).levels[1];
+        assert_eq!(level[(0, 0)], Color::new(0.8, 0.8, 0.8));
+        assert_eq!(level[(1, 0)], Color::new(3.2, 3.2, 3.2));
+    }
+
+    #[test]
+    fn test_level_of_detail() {
+        let mut texture = Texture::new(checkers(8));
+        assert_eq!(texture.level_of_detail(1.0 / 8.0), 0.0);
+        assert_eq!(texture.level_of_detail(1.0 / 4.0), 1.0);
+        assert_eq!(texture.level_of_detail(1.0 / 64.0), 0.0);
+        assert_eq!(texture.level_of_detail(4.0), 3.0);
+
+        texture.bias = 1.0;
+        assert_eq!(texture.level_of_detail(1.0 / 8.0), 1.0);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut image = Canvas::new(2, 2);
+        image.write_pixel(0, 1, Color::new(1.0, 0.0, 0.0));
+        image.write_pixel(1, 0, Color::new(0.0, 1.0, 0.0));
+        let texture = Texture::new(image);
+
+        // v points up, so the bottom-left texel is row 1.
+        assert_eq!(texture.sample(0.25, 0.25, 0.0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(texture.sample(0.75, 0.75, 0.0), Color::new(0.0, 1.0, 0.0));
+        // coordinates repeat.
+        assert_eq!(texture.sample(1.25, -0.75, 0.0), Color::new(1.0, 0.0, 0.0));
+        // a footprint covering the whole texture reads the 1x1 level.
+        assert_eq!(texture.sample(0.25, 0.25, 1.0), Color::new(0.25, 0.25, 0.0));
+    }
+
+    #[test]
+    fn test_bilinear_sample() {
+        let mut image = Canvas::new(2, 1);
+        image.write_pixel(1, 0, Color::new(1.0, 1.0, 1.0));
+        let mut texture = Texture::new(image);
+        texture.filter = TextureFilter::Bilinear;
+
+        // texel centers read back exactly.
+        assert_eq!(texture.sample(0.25, 0.5, 0.0), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(texture.sample(0.75, 0.5, 0.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(texture.sample(0.5, 0.5, 0.0), Color::new(0.5, 0.5, 0.5));
+        // halfway across the seam blends the last texel with the first.
+        assert_eq!(texture.sample(0.0, 0.5, 0.0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(
+            texture.sample(0.875, 0.5, 0.0),
+            Color::new(0.75, 0.75, 0.75)
+        );
+    }
+
+    #[test]
+    fn test_trilinear_sample() {
+        let mut texture = Texture::new(checkers(8));
+        texture.filter = TextureFilter::Trilinear;
+
+        // level 0 at a texel center is pure white, level 1 is grey, so half a
+        // level in between lands halfway.
+        let footprint = (2.0 as Float).sqrt() / 8.0;
+        assert_float_eq!(
+            texture.level_of_detail(footprint),
+            0.5,
+            abs <= approx::DEFAULT_EPSILON
+        );
+        assert_eq!(
+            texture.sample(1.0 / 16.0, 15.0 / 16.0, footprint),
+            Color::new(0.75, 0.75, 0.75)
+        );
+        assert_eq!(
+            texture.sample(1.0 / 16.0, 15.0 / 16.0, 0.0),
+            Color::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot build a texture from an empty canvas!")]
+    fn test_empty_texture() {
+        Texture::new(Canvas::new(0, 4));
+    }
+
+    #[test]
+    fn test_load_empty_texture() {
+        let dir = std::env::temp_dir().join("renachan_test_load_empty_texture");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("empty.ppm");
+        std::fs::write(&path, "P3\n0 0\n255\n").unwrap();
+
+        assert!(matches!(Texture::load(&path), Err(Error::EmptyImage)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_texture_cache() {
+        let dir = std::env::temp_dir().join("renachan_test_texture_cache");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("checkers.ppm");
+        checkers(4).write_to_ppm(&path).unwrap();
+
+        let mut cache = TextureCache::new();
+        let a = cache.load(&path).unwrap();
+        let b = cache.load(&path).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        let c = cache
+            .load(
+                &dir.join(".")
+                    .join("..")
+                    .join(dir.file_name().unwrap())
+                    .join("checkers.ppm"),
+            )
+            .unwrap();
+        assert!(Arc::ptr_eq(&a, &c));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(a.levels[0], checkers(4));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            cache.load(Path::new("missing.png")),
+            Err(Error::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            cache.load(Path::new("missing.ppm")),
+            Err(Error::Io(_))
+        ));
+        assert_eq!(cache.len(), 1);
+    }
+}
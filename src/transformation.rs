@@ -1,11 +1,11 @@
-use crate::matrix::Matrix;
+use crate::{approx, matrix::Matrix4, tuple::Tuple, Float};
 
 // TODO: implement a fluent API for this
 // e.g. transform = matrix.rotate_x().scale().translate();
 // basically letting u chain method calls instead of just functions.
 // good exercise for refactoring :p
-pub fn translation(x: f64, y: f64, z: f64) -> Matrix {
-    let mut matrix = Matrix::identity_matrix(4);
+pub fn translation(x: Float, y: Float, z: Float) -> Matrix4 {
+    let mut matrix = Matrix4::identity();
     matrix[(0, 3)] = x;
     matrix[(1, 3)] = y;
     matrix[(2, 3)] = z;
@@ -13,8 +13,8 @@ pub fn translation(x: f64, y: f64, z: f64) -> Matrix {
     matrix
 }
 
-pub fn scaling(x: f64, y: f64, z: f64) -> Matrix {
-    let mut matrix = Matrix::identity_matrix(4);
+pub fn scaling(x: Float, y: Float, z: Float) -> Matrix4 {
+    let mut matrix = Matrix4::identity();
     matrix[(0, 0)] = x;
     matrix[(1, 1)] = y;
     matrix[(2, 2)] = z;
@@ -22,8 +22,8 @@ pub fn scaling(x: f64, y: f64, z: f64) -> Matrix {
     matrix
 }
 
-pub fn rotation_x(angle: f64) -> Matrix {
-    let mut matrix = Matrix::identity_matrix(4);
+pub fn rotation_x(angle: Float) -> Matrix4 {
+    let mut matrix = Matrix4::identity();
     matrix[(1, 1)] = angle.cos();
     matrix[(1, 2)] = -angle.sin();
     matrix[(2, 1)] = angle.sin();
@@ -32,8 +32,8 @@ pub fn rotation_x(angle: f64) -> Matrix {
     matrix
 }
 
-pub fn rotation_y(angle: f64) -> Matrix {
-    let mut matrix = Matrix::identity_matrix(4);
+pub fn rotation_y(angle: Float) -> Matrix4 {
+    let mut matrix = Matrix4::identity();
     matrix[(0, 0)] = angle.cos();
     matrix[(0, 2)] = angle.sin();
     matrix[(2, 0)] = -angle.sin();
@@ -42,8 +42,8 @@ pub fn rotation_y(angle: f64) -> Matrix {
     matrix
 }
 
-pub fn rotation_z(angle: f64) -> Matrix {
-    let mut matrix = Matrix::identity_matrix(4);
+pub fn rotation_z(angle: Float) -> Matrix4 {
+    let mut matrix = Matrix4::identity();
     matrix[(0, 0)] = angle.cos();
     matrix[(0, 1)] = -angle.sin();
     matrix[(1, 0)] = angle.sin();
@@ -52,8 +52,34 @@ pub fn rotation_z(angle: f64) -> Matrix {
     matrix
 }
 
-pub fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix {
-    let mut matrix = Matrix::identity_matrix(4);
+// rodrigues' rotation formula, rotating counter-clockwise around `axis` the
+// same way rotation_x/y/z do around the basis axes.
+pub fn rotation_axis(axis: Tuple, angle: Float) -> Matrix4 {
+    let axis = Tuple::vector(axis.x, axis.y, axis.z);
+    if axis.magnitude() == 0.0 {
+        panic!("cannot rotate around a zero-length axis");
+    }
+
+    let Tuple { x, y, z, .. } = axis.normalize();
+    let (s, c) = angle.sin_cos();
+    let t = 1.0 - c;
+
+    let mut matrix = Matrix4::identity();
+    matrix[(0, 0)] = c + x * x * t;
+    matrix[(0, 1)] = x * y * t - z * s;
+    matrix[(0, 2)] = x * z * t + y * s;
+    matrix[(1, 0)] = y * x * t + z * s;
+    matrix[(1, 1)] = c + y * y * t;
+    matrix[(1, 2)] = y * z * t - x * s;
+    matrix[(2, 0)] = z * x * t - y * s;
+    matrix[(2, 1)] = z * y * t + x * s;
+    matrix[(2, 2)] = c + z * z * t;
+
+    matrix
+}
+
+pub fn shearing(xy: Float, xz: Float, yx: Float, yz: Float, zx: Float, zy: Float) -> Matrix4 {
+    let mut matrix = Matrix4::identity();
     matrix[(0, 1)] = xy;
     matrix[(0, 2)] = xz;
     matrix[(1, 0)] = yx;
@@ -64,12 +90,99 @@ pub fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Matrix
     matrix
 }
 
+// a transform split into translation * rotation * shear * scaling. `rotation`
+// is always a proper rotation, and `shear` holds the xy, xz and yz factors in
+// the order `shearing` takes them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Decomposition {
+    pub translation: Tuple,
+    pub rotation: Matrix4,
+    pub shear: Tuple,
+    pub scale: Tuple,
+}
+
+impl Decomposition {
+    pub fn compose(&self) -> Matrix4 {
+        translation(self.translation.x, self.translation.y, self.translation.z)
+            * self.rotation
+            * shearing(self.shear.x, self.shear.y, 0.0, self.shear.z, 0.0, 0.0)
+            * scaling(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl Matrix4 {
+    // `None` unless the matrix is affine (bottom row 0 0 0 1) and its upper 3x3
+    // is invertible, since otherwise there's no rotation to pull out.
+    pub fn decompose(&self) -> Option<Decomposition> {
+        let bottom = [self[(3, 0)], self[(3, 1)], self[(3, 2)], self[(3, 3)]];
+        if bottom
+            .iter()
+            .zip([0.0, 0.0, 0.0, 1.0])
+            .any(|(a, b)| (a - b).abs() > approx::epsilon())
+        {
+            return None;
+        }
+
+        let translation = Tuple::vector(self[(0, 3)], self[(1, 3)], self[(2, 3)]);
+        let column = |col: usize| Tuple::vector(self[(0, col)], self[(1, col)], self[(2, col)]);
+
+        // gram-schmidt, peeling the shear off each column against the ones
+        // already orthonormalised. shear factors are relative to the scale of
+        // the column they came from.
+        let mut scale = [0.0; 3];
+        let mut shear = [0.0; 3];
+        let mut axes = [Tuple::vector(0.0, 0.0, 0.0); 3];
+        for col in 0..3 {
+            let mut c = column(col);
+            let mut factors = vec![];
+            for axis in &axes[..col] {
+                let h = *axis * c;
+                c -= *axis * h;
+                factors.push(h);
+            }
+
+            scale[col] = c.magnitude();
+            if scale[col] <= approx::epsilon() {
+                return None;
+            }
+            axes[col] = c / scale[col];
+            // column 1 gives xy, column 2 gives xz then yz.
+            for (prev, h) in factors.into_iter().enumerate() {
+                shear[prev + col - 1] = h / scale[col];
+            }
+        }
+
+        // a mirrored transform leaves a left-handed basis, which gets folded
+        // into a negative x scale.
+        if axes[0] * axes[1].cross(&axes[2]) < 0.0 {
+            axes[0] = -axes[0];
+            scale[0] = -scale[0];
+            shear[0] = -shear[0];
+            shear[1] = -shear[1];
+        }
+
+        let mut rotation = Matrix4::identity();
+        for (col, axis) in axes.iter().enumerate() {
+            rotation[(0, col)] = axis.x;
+            rotation[(1, col)] = axis.y;
+            rotation[(2, col)] = axis.z;
+        }
+
+        Some(Decomposition {
+            translation,
+            rotation,
+            shear: Tuple::vector(shear[0], shear[1], shear[2]),
+            scale: Tuple::vector(scale[0], scale[1], scale[2]),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::f64::consts::PI;
-
     use super::*;
-    use crate::tuple::Tuple;
+    use crate::consts::PI;
+    use crate::tuple::{Point, Vector};
+    use float_eq::assert_float_eq;
 
     #[test]
     fn test_mul_translation_matrix() {
@@ -96,6 +209,18 @@ mod tests {
         assert_eq!(transform * v, v);
     }
 
+    #[test]
+    fn test_translation_typed() {
+        let transform = translation(5.0, -3.0, 2.0);
+        let v = Vector::new(-3.0, 4.0, 5.0);
+
+        assert_eq!(
+            transform * Point::new(-3.0, 4.0, 5.0),
+            Point::new(2.0, 1.0, 7.0)
+        );
+        assert_eq!(transform * v, v);
+    }
+
     #[test]
     fn test_mul_scaling_matrix_point() {
         let transform = scaling(2.0, 3.0, 4.0);
@@ -112,7 +237,6 @@ mod tests {
         assert_eq!(transform * v, Tuple::vector(-8.0, 18.0, 32.0));
     }
 
-    // FIXME: this fails due to floating point imprecision and all of that.
     #[test]
     fn test_mul_scaling_matrix_inverse() {
         let transform = scaling(2.0, 3.0, 4.0);
@@ -140,8 +264,8 @@ mod tests {
             half_quarter * p,
             Tuple::point(
                 0.0,
-                (2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
-                (2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0
+                (2.0 as Float).sqrt() / 2.0,
+                (2.0 as Float).sqrt() / 2.0
             )
         );
         assert_eq!(full_quarter * p, Tuple::point(0.0, 0.0, 1.0));
@@ -157,8 +281,8 @@ mod tests {
             inverse * p,
             Tuple::point(
                 0.0,
-                (2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
-                -(2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
+                (2.0 as Float).sqrt() / 2.0,
+                -(2.0 as Float).sqrt() / 2.0,
             )
         );
     }
@@ -172,9 +296,9 @@ mod tests {
         assert_eq!(
             half_quarter * p,
             Tuple::point(
-                (2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
+                (2.0 as Float).sqrt() / 2.0,
                 0.0,
-                (2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
+                (2.0 as Float).sqrt() / 2.0,
             )
         );
         assert_eq!(full_quarter * p, Tuple::point(1.0, 0.0, 0.0));
@@ -189,14 +313,49 @@ mod tests {
         assert_eq!(
             half_quarter * p,
             Tuple::point(
-                -(2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
-                (2.0_f64.sqrt() / 2.0 * 100000.0).round() / 100000.0,
+                -(2.0 as Float).sqrt() / 2.0,
+                (2.0 as Float).sqrt() / 2.0,
                 0.0,
             )
         );
         assert_eq!(full_quarter * p, Tuple::point(-1.0, 0.0, 0.0));
     }
 
+    #[test]
+    fn test_rotation_axis_basis() {
+        let angle = PI / 3.0;
+
+        assert_eq!(
+            rotation_axis(Tuple::vector(1.0, 0.0, 0.0), angle),
+            rotation_x(angle)
+        );
+        assert_eq!(
+            rotation_axis(Tuple::vector(0.0, 2.0, 0.0), angle),
+            rotation_y(angle)
+        );
+        assert_eq!(
+            rotation_axis(Tuple::vector(0.0, 0.0, 1.0), angle),
+            rotation_z(angle)
+        );
+    }
+
+    #[test]
+    fn test_rotation_axis_diagonal() {
+        let r = rotation_axis(Tuple::vector(1.0, 1.0, 1.0), 2.0 * PI / 3.0);
+
+        assert_eq!(r * Tuple::point(1.0, 0.0, 0.0), Tuple::point(0.0, 1.0, 0.0));
+        assert_eq!(
+            r * Tuple::vector(0.0, 0.0, 1.0),
+            Tuple::vector(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot rotate around a zero-length axis")]
+    fn test_rotation_axis_zero() {
+        rotation_axis(Tuple::vector(0.0, 0.0, 0.0), PI);
+    }
+
     #[test]
     fn test_shearing_xy() {
         let transform = shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
@@ -272,4 +431,59 @@ mod tests {
 
         assert_eq!(t * p, Tuple::point(15.0, 0.0, 7.0));
     }
+
+    #[test]
+    fn test_decompose() {
+        let r = rotation_axis(Tuple::vector(1.0, 2.0, 3.0), 0.7);
+        let t = translation(1.0, -2.0, 3.0) * r * scaling(2.0, 3.0, 0.5);
+        let d = t.decompose().unwrap();
+
+        assert_eq!(d.translation, Tuple::vector(1.0, -2.0, 3.0));
+        assert_eq!(d.scale, Tuple::vector(2.0, 3.0, 0.5));
+        assert_eq!(d.shear, Tuple::vector(0.0, 0.0, 0.0));
+        assert_eq!(d.rotation, r);
+        assert_eq!(d.compose(), t);
+    }
+
+    #[test]
+    fn test_decompose_shear() {
+        let r = rotation_z(0.3) * rotation_x(-1.1);
+        let t = translation(4.0, 0.0, -1.0)
+            * r
+            * shearing(0.5, -0.25, 0.0, 2.0, 0.0, 0.0)
+            * scaling(1.0, 2.0, 3.0);
+        let d = t.decompose().unwrap();
+
+        assert_eq!(d.shear, Tuple::vector(0.5, -0.25, 2.0));
+        assert_eq!(d.scale, Tuple::vector(1.0, 2.0, 3.0));
+        assert_eq!(d.rotation, r);
+        assert_eq!(d.compose(), t);
+
+        // shear in the lower triangle comes out as a different, but still
+        // rigid, rotation along with upper triangle shear.
+        let t = shearing(0.0, 0.0, 1.0, 0.0, 0.5, 0.0);
+        let d = t.decompose().unwrap();
+        assert_eq!(d.rotation * d.rotation.transpose(), Matrix4::identity());
+        assert_float_eq!(d.rotation.determinant(), 1.0, abs <= approx::epsilon());
+        assert_eq!(d.compose(), t);
+    }
+
+    #[test]
+    fn test_decompose_degenerate() {
+        assert_eq!(scaling(1.0, 0.0, 1.0).decompose(), None);
+
+        let mut projective = Matrix4::identity();
+        projective[(3, 2)] = 1.0;
+        assert_eq!(projective.decompose(), None);
+    }
+
+    #[test]
+    fn test_decompose_reflection() {
+        let t = rotation_y(PI / 4.0) * scaling(-1.0, 2.0, 2.0);
+        let d = t.decompose().unwrap();
+
+        assert_eq!(d.scale, Tuple::vector(-1.0, 2.0, 2.0));
+        assert_eq!(d.rotation, rotation_y(PI / 4.0));
+        assert_eq!(d.compose(), t);
+    }
 }
@@ -1,5 +1,6 @@
+use crate::{approx, simd, Float};
 use float_eq::{derive_float_eq, float_eq};
-use std::f64::{self, EPSILON};
+use std::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
 #[derive_float_eq(
@@ -7,26 +8,26 @@ use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
     ulps_tol_derive = "Clone, Copy, Debug, PartialEq",
     debug_ulps_diff = "TupleDebugUlpsDiff",
     debug_ulps_diff_derive = "Clone, Copy, Debug, PartialEq",
-    all_tol = "f64"
+    all_tol = "Float"
 )]
 #[derive(Clone, Copy, Debug)]
 pub struct Tuple {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
-    pub w: f64,
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+    pub w: Float,
 }
 
 impl Tuple {
-    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+    pub fn new(x: Float, y: Float, z: Float, w: Float) -> Self {
         Self { x, y, z, w }
     }
 
-    pub fn point(x: f64, y: f64, z: f64) -> Self {
+    pub fn point(x: Float, y: Float, z: Float) -> Self {
         Self { x, y, z, w: 1.0 }
     }
 
-    pub fn vector(x: f64, y: f64, z: f64) -> Self {
+    pub fn vector(x: Float, y: Float, z: Float) -> Self {
         Self { x, y, z, w: 0.0 }
     }
 
@@ -38,7 +39,7 @@ impl Tuple {
         self.w == 0.0
     }
 
-    pub fn magnitude(&self) -> f64 {
+    pub fn magnitude(&self) -> Float {
         (self.x.powf(2.0) + self.y.powf(2.0) + self.z.powf(2.0) + self.w.powf(2.0)).sqrt()
     }
 
@@ -58,18 +59,105 @@ impl Tuple {
             self.x * other.y - self.y * other.x,
         )
     }
+
+    pub fn reflect(&self, normal: Tuple) -> Self {
+        *self - normal * 2.0 * (*self * normal)
+    }
+
+    // angle in radians, clamped so rounding can't push acos out of its domain.
+    pub fn angle_between(&self, other: &Tuple) -> Float {
+        let cos = (*self * *other) / (self.magnitude() * other.magnitude());
+        cos.clamp(-1.0, 1.0).acos()
+    }
+
+    // component of self that lies along `other`.
+    pub fn project_onto(&self, other: &Tuple) -> Self {
+        let length_squared = *other * *other;
+        if length_squared == 0.0 {
+            panic!("cannot project onto a zero vector!");
+        }
+
+        *other * ((*self * *other) / length_squared)
+    }
+
+    // component of self perpendicular to `other`.
+    pub fn reject_from(&self, other: &Tuple) -> Self {
+        *self - self.project_onto(other)
+    }
+
+    pub fn lerp(a: Tuple, b: Tuple, t: Float) -> Self {
+        a + (b - a) * t
+    }
+
+    // the component-wise helpers below only touch x, y and z, so a point stays
+    // a point and a vector stays a vector.
+    pub fn clamp(&self, min: Float, max: Float) -> Self {
+        Self {
+            x: self.x.clamp(min, max),
+            y: self.y.clamp(min, max),
+            z: self.z.clamp(min, max),
+            w: self.w,
+        }
+    }
+
+    pub fn min(&self, other: &Tuple) -> Self {
+        Self {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+            z: self.z.min(other.z),
+            w: self.w,
+        }
+    }
+
+    pub fn max(&self, other: &Tuple) -> Self {
+        Self {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+            z: self.z.max(other.z),
+            w: self.w,
+        }
+    }
 }
 
-impl PartialEq for Tuple {
-    fn eq(&self, other: &Self) -> bool {
-        let cmp = Tuple {
-            x: 1.0 * EPSILON,
-            y: 1.0 * EPSILON,
-            z: 1.0 * EPSILON,
-            w: 1.0 * EPSILON,
+impl fmt::Display for Tuple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |x: Float| match f.precision() {
+            Some(p) => format!("{:.*}", p, x),
+            None => format!("{}", x),
         };
 
-        float_eq!(self, other, abs <= cmp)
+        if self.is_point() {
+            write!(
+                f,
+                "point({}, {}, {})",
+                value(self.x),
+                value(self.y),
+                value(self.z)
+            )
+        } else if self.is_vector() {
+            write!(
+                f,
+                "vector({}, {}, {})",
+                value(self.x),
+                value(self.y),
+                value(self.z)
+            )
+        } else {
+            write!(
+                f,
+                "tuple({}, {}, {}, {})",
+                value(self.x),
+                value(self.y),
+                value(self.z),
+                value(self.w)
+            )
+        }
+    }
+}
+
+impl PartialEq for Tuple {
+    fn eq(&self, other: &Self) -> bool {
+        float_eq!(self, other, abs_all <= approx::epsilon())
     }
 }
 
@@ -79,23 +167,13 @@ impl Add for Tuple {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
-            w: self.w + other.w,
-        }
+        simd::add4(self.into(), other.into()).into()
     }
 }
 
 impl AddAssign for Tuple {
     fn add_assign(&mut self, other: Self) {
-        *self = Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
-            w: self.w + other.w,
-        }
+        *self = *self + other
     }
 }
 
@@ -103,23 +181,13 @@ impl Sub for Tuple {
     type Output = Self;
 
     fn sub(self, other: Self) -> Self {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-            w: self.w - other.w,
-        }
+        simd::sub4(self.into(), other.into()).into()
     }
 }
 
 impl SubAssign for Tuple {
     fn sub_assign(&mut self, other: Self) {
-        *self = Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-            w: self.w - other.w,
-        }
+        *self = *self - other
     }
 }
 
@@ -127,46 +195,232 @@ impl Neg for Tuple {
     type Output = Self;
 
     fn neg(self) -> Self {
-        Self {
-            x: -self.x,
-            y: -self.y,
-            z: -self.z,
-            w: -self.w,
-        }
+        simd::neg4(self.into()).into()
     }
 }
 
-impl Mul<f64> for Tuple {
+impl Mul<Float> for Tuple {
     type Output = Self;
 
-    fn mul(self, other: f64) -> Self {
-        Self {
-            x: self.x * other,
-            y: self.y * other,
-            z: self.z * other,
-            w: self.w * other,
-        }
+    fn mul(self, other: Float) -> Self {
+        simd::scale4(self.into(), other).into()
     }
 }
 
 impl Mul<Tuple> for Tuple {
-    type Output = f64;
+    type Output = Float;
 
-    fn mul(self, other: Tuple) -> f64 {
-        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    fn mul(self, other: Tuple) -> Float {
+        simd::dot4(self.into(), other.into())
     }
 }
 
-impl Div<f64> for Tuple {
+impl Div<Float> for Tuple {
     type Output = Self;
 
-    fn div(self, other: f64) -> Self {
-        Self {
-            x: self.x / other,
-            y: self.y / other,
-            z: self.z / other,
-            w: self.w / other,
-        }
+    fn div(self, other: Float) -> Self {
+        simd::div4(self.into(), other).into()
+    }
+}
+
+impl From<[Float; 4]> for Tuple {
+    fn from([x, y, z, w]: [Float; 4]) -> Self {
+        Self { x, y, z, w }
+    }
+}
+
+impl From<Tuple> for [Float; 4] {
+    fn from(t: Tuple) -> Self {
+        [t.x, t.y, t.z, t.w]
+    }
+}
+
+// a position. only the operations that make sense geometrically exist, so
+// point - point gives a vector and a point can only be moved by a vector.
+#[derive(Clone, Copy, Debug)]
+pub struct Point {
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+}
+
+// a direction, see `Point`.
+#[derive(Clone, Copy, Debug)]
+pub struct Vector {
+    pub x: Float,
+    pub y: Float,
+    pub z: Float,
+}
+
+impl Point {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl Vector {
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn magnitude(&self) -> Float {
+        Tuple::from(*self).magnitude()
+    }
+
+    pub fn normalize(&self) -> Self {
+        Tuple::from(*self).normalize().into_vector()
+    }
+
+    pub fn dot(&self, other: &Vector) -> Float {
+        Tuple::from(*self) * Tuple::from(*other)
+    }
+
+    pub fn cross(&self, other: &Vector) -> Self {
+        Tuple::from(*self).cross(&(*other).into()).into_vector()
+    }
+
+    pub fn reflect(&self, normal: Vector) -> Self {
+        Tuple::from(*self).reflect(normal.into()).into_vector()
+    }
+}
+
+impl Tuple {
+    // only for results that are known to keep w, e.g. ops between two
+    // vectors or a point and a vector.
+    fn into_point(self) -> Point {
+        Point::new(self.x, self.y, self.z)
+    }
+
+    fn into_vector(self) -> Vector {
+        Vector::new(self.x, self.y, self.z)
+    }
+}
+
+impl From<Point> for Tuple {
+    fn from(p: Point) -> Self {
+        Tuple::point(p.x, p.y, p.z)
+    }
+}
+
+impl From<Vector> for Tuple {
+    fn from(v: Vector) -> Self {
+        Tuple::vector(v.x, v.y, v.z)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&Tuple::from(*self), f)
+    }
+}
+
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&Tuple::from(*self), f)
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Self) -> bool {
+        Tuple::from(*self) == Tuple::from(*other)
+    }
+}
+
+impl Eq for Point {}
+
+impl PartialEq for Vector {
+    fn eq(&self, other: &Self) -> bool {
+        Tuple::from(*self) == Tuple::from(*other)
+    }
+}
+
+impl Eq for Vector {}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Self) -> Vector {
+        (Tuple::from(self) - Tuple::from(other)).into_vector()
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Self;
+
+    fn add(self, other: Vector) -> Self {
+        (Tuple::from(self) + Tuple::from(other)).into_point()
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Self;
+
+    fn sub(self, other: Vector) -> Self {
+        (Tuple::from(self) - Tuple::from(other)).into_point()
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, other: Vector) {
+        *self = *self - other
+    }
+}
+
+impl Add for Vector {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        (Tuple::from(self) + Tuple::from(other)).into_vector()
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other
+    }
+}
+
+impl Sub for Vector {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        (Tuple::from(self) - Tuple::from(other)).into_vector()
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other
+    }
+}
+
+impl Neg for Vector {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        (-Tuple::from(self)).into_vector()
+    }
+}
+
+impl Mul<Float> for Vector {
+    type Output = Self;
+
+    fn mul(self, other: Float) -> Self {
+        (Tuple::from(self) * other).into_vector()
+    }
+}
+
+impl Div<Float> for Vector {
+    type Output = Self;
+
+    fn div(self, other: Float) -> Self {
+        (Tuple::from(self) / other).into_vector()
     }
 }
 
@@ -182,8 +436,8 @@ mod tests {
         assert_eq!(a.y, -4.2);
         assert_eq!(a.z, 3.1);
         assert_eq!(a.w, 1.0);
-        assert_eq!(a.is_point(), true);
-        assert_eq!(a.is_vector(), false);
+        assert!(a.is_point());
+        assert!(!a.is_vector());
     }
 
     #[test]
@@ -193,8 +447,8 @@ mod tests {
         assert_eq!(a.y, -4.2);
         assert_eq!(a.z, 3.1);
         assert_eq!(a.w, 0.0);
-        assert_eq!(a.is_point(), false);
-        assert_eq!(a.is_vector(), true);
+        assert!(!a.is_point());
+        assert!(a.is_vector());
     }
 
     #[test]
@@ -283,8 +537,8 @@ mod tests {
         let a = Tuple::vector(1.0, 0.0, 0.0);
         let b = Tuple::vector(-1.0, -2.0, -3.0);
 
-        assert_float_eq!(a.magnitude(), 1.0, abs <= EPSILON);
-        assert_float_eq!(b.magnitude(), (14.0_f64).sqrt(), abs <= EPSILON);
+        assert_float_eq!(a.magnitude(), 1.0, abs <= Float::EPSILON);
+        assert_float_eq!(b.magnitude(), (14.0 as Float).sqrt(), abs <= Float::EPSILON);
     }
 
     #[test]
@@ -301,7 +555,7 @@ mod tests {
         let b = Tuple::vector(2.0, 3.0, 4.0);
         let result = 20.0;
 
-        assert_float_eq!(a * b, result, abs <= EPSILON)
+        assert_float_eq!(a * b, result, abs <= Float::EPSILON)
     }
 
     #[test]
@@ -314,4 +568,133 @@ mod tests {
         assert_eq!(a.cross(&b), result_ab);
         assert_eq!(b.cross(&a), result_ba);
     }
+
+    #[test]
+    fn test_reflect_45_degrees() {
+        let v = Tuple::vector(1.0, -1.0, 0.0);
+        let n = Tuple::vector(0.0, 1.0, 0.0);
+
+        assert_eq!(v.reflect(n), Tuple::vector(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_reflect_slanted() {
+        let v = Tuple::vector(0.0, -1.0, 0.0);
+        let n = Tuple::vector(
+            (2.0 as Float).sqrt() / 2.0,
+            (2.0 as Float).sqrt() / 2.0,
+            0.0,
+        );
+
+        assert_eq!(v.reflect(n), Tuple::vector(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_angle_between() {
+        let x = Tuple::vector(1.0, 0.0, 0.0);
+
+        assert_float_eq!(
+            x.angle_between(&Tuple::vector(0.0, 3.0, 0.0)),
+            crate::consts::FRAC_PI_2,
+            abs <= approx::DEFAULT_EPSILON
+        );
+        assert_float_eq!(
+            x.angle_between(&Tuple::vector(1.0, 1.0, 0.0)),
+            crate::consts::FRAC_PI_4,
+            abs <= approx::DEFAULT_EPSILON
+        );
+        assert_float_eq!(
+            x.angle_between(&(x * 2.0)),
+            0.0,
+            abs <= approx::DEFAULT_EPSILON
+        );
+    }
+
+    #[test]
+    fn test_project_reject() {
+        let v = Tuple::vector(3.0, 4.0, 5.0);
+        let onto = Tuple::vector(0.0, 2.0, 0.0);
+
+        assert_eq!(v.project_onto(&onto), Tuple::vector(0.0, 4.0, 0.0));
+        assert_eq!(v.reject_from(&onto), Tuple::vector(3.0, 0.0, 5.0));
+        assert_eq!(v.project_onto(&onto) + v.reject_from(&onto), v);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot project onto a zero vector!")]
+    fn test_project_onto_zero() {
+        Tuple::vector(1.0, 2.0, 3.0).project_onto(&Tuple::vector(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot project onto a zero vector!")]
+    fn test_reject_from_zero() {
+        Tuple::vector(1.0, 2.0, 3.0).reject_from(&Tuple::vector(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_lerp() {
+        let a = Tuple::point(0.0, 2.0, -4.0);
+        let b = Tuple::point(1.0, 4.0, 4.0);
+
+        assert_eq!(Tuple::lerp(a, b, 0.0), a);
+        assert_eq!(Tuple::lerp(a, b, 1.0), b);
+        assert_eq!(Tuple::lerp(a, b, 0.25), Tuple::point(0.25, 2.5, -2.0));
+    }
+
+    #[test]
+    fn test_clamp_min_max() {
+        let a = Tuple::vector(-1.5, 0.5, 2.0);
+        let b = Tuple::vector(1.0, -1.0, 3.0);
+
+        assert_eq!(a.clamp(0.0, 1.0), Tuple::vector(0.0, 0.5, 1.0));
+        assert_eq!(a.min(&b), Tuple::vector(-1.5, -1.0, 2.0));
+        assert_eq!(a.max(&b), Tuple::vector(1.0, 0.5, 3.0));
+        assert!(Tuple::point(5.0, 5.0, 5.0).clamp(0.0, 0.5).is_point());
+    }
+
+    #[test]
+    fn test_display_tuple() {
+        assert_eq!(
+            Tuple::point(1.0, -2.5, 3.0).to_string(),
+            "point(1, -2.5, 3)"
+        );
+        assert_eq!(
+            format!("{:.2}", Tuple::vector(1.0, 0.5, 0.0)),
+            "vector(1.00, 0.50, 0.00)"
+        );
+        assert_eq!(
+            Tuple::new(1.0, 2.0, 3.0, 4.0).to_string(),
+            "tuple(1, 2, 3, 4)"
+        );
+    }
+
+    #[test]
+    fn test_point_vector_ops() {
+        let p = Point::new(3.0, 2.0, 1.0);
+        let q = Point::new(5.0, 6.0, 7.0);
+        let v = Vector::new(5.0, 6.0, 7.0);
+
+        assert_eq!(p - q, Vector::new(-2.0, -4.0, -6.0));
+        assert_eq!(p + v, Point::new(8.0, 8.0, 8.0));
+        assert_eq!(p - v, Point::new(-2.0, -4.0, -6.0));
+        assert_eq!(v - v, Vector::new(0.0, 0.0, 0.0));
+        assert_eq!(-v * 2.0, Vector::new(-10.0, -12.0, -14.0));
+        assert_eq!(Tuple::from(p), Tuple::point(3.0, 2.0, 1.0));
+        assert_eq!(Tuple::from(v), Tuple::vector(5.0, 6.0, 7.0));
+    }
+
+    #[test]
+    fn test_typed_vector_products() {
+        let a = Vector::new(1.0, 2.0, 3.0);
+        let b = Vector::new(2.0, 3.0, 4.0);
+
+        assert_float_eq!(a.dot(&b), 20.0, abs <= Float::EPSILON);
+        assert_eq!(a.cross(&b), Vector::new(-1.0, 2.0, -1.0));
+        assert_eq!(
+            Vector::new(4.0, 0.0, 0.0).normalize(),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(Point::new(1.0, 2.0, 3.0).to_string(), "point(1, 2, 3)");
+    }
 }